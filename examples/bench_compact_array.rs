@@ -59,5 +59,31 @@ pub fn main() {
         pl.done_with_count(args.n);
     }
 
+    let len = a.len();
+    let mut buffer = vec![0; len];
+    for _ in 0..args.repeats {
+        pl.item_name = "get";
+        pl.start("Scanning with get...");
+        for i in 0..len {
+            unsafe {
+                u += a.get_unchecked(i);
+            }
+        }
+        pl.done_with_count(len);
+
+        pl.item_name = "next";
+        pl.start("Scanning with an iterator...");
+        for value in a.iter() {
+            u += value;
+        }
+        pl.done_with_count(len);
+
+        pl.item_name = "value";
+        pl.start("Copying...");
+        a.copy_to(&mut buffer);
+        u += buffer[len / 2];
+        pl.done_with_count(len);
+    }
+
     black_box(u);
 }
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use epserde::prelude::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use sux::mph::gov::GOVMPH;
+use sux::sf::gov3::GOV3;
+
+#[derive(ValueEnum, Clone, Debug)]
+enum Kind {
+    /// A minimal perfect hash function (`.cmph`)
+    Mph,
+    /// A static function (`.csf`)
+    Sf,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Convert GOV structures dumped in C-compatible format to ε-serde format", long_about = None)]
+struct Args {
+    /// The kind of structure to convert.
+    kind: Kind,
+    /// The file containing the C-compatible dump.
+    input: String,
+    /// The ε-serde file to write.
+    output: String,
+}
+
+fn main() -> Result<()> {
+    stderrlog::new()
+        .verbosity(2)
+        .timestamp(stderrlog::Timestamp::Second)
+        .init()
+        .unwrap();
+
+    let args = Args::parse();
+
+    let mut file = BufWriter::new(File::create(&args.output)?);
+    match args.kind {
+        Kind::Mph => GOVMPH::load(&args.input)?.serialize(&mut file)?,
+        Kind::Sf => GOV3::load(&args.input)?.serialize(&mut file)?,
+    };
+    file.flush()?;
+    Ok(())
+}
@@ -0,0 +1,363 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Streams of bits supporting fixed-width fields and instantaneous codes.
+
+A [`BitWriter`] appends fields to a [`BitVec`], and a [`BitReader`] reads
+them back from any backend implementing `AsRef<[usize]>`, such as a
+[`BitVec`], a slice, or a memory-mapped structure. Bits are written
+starting from the lowest bit of each word, consistently with the indexing
+of [`BitVec`]; fixed-width fields are written starting from their
+lowest bit.
+
+The following codes are available, all of them representing natural numbers:
+
+- unary: `x` is written as `x` zeros followed by a one;
+- [Elias γ](https://en.wikipedia.org/wiki/Elias_gamma_coding): `x + 1` is
+  written as the unary code of its length in bits minus one, followed by
+  its bits, except for the most significant one;
+- [Elias δ](https://en.wikipedia.org/wiki/Elias_delta_coding): as γ, but
+  the length is written in γ code;
+- [ζ<sub>k</sub>](https://doi.org/10.1080/15427951.2005.10129113): `x + 1`
+  is written as the unary code of ⌊⌊log₂(`x` + 1)⌋ / `k`⌋ = `h`, followed
+  by a minimal binary code of `x` + 1 − 2<sup>`hk`</sup> in
+  [0..2<sup>(`h` + 1)`k`</sup> − 2<sup>`hk`</sup>);
+- [Golomb](https://en.wikipedia.org/wiki/Golomb_coding) with modulus `b`:
+  the unary code of `x` / `b` followed by a minimal binary code of `x` % `b`
+  in [0..`b`).
+
+Minimal binary codes of `v` in [0..`z`), where `s` = ⌈log₂ `z`⌉ and
+`m` = 2<sup>`s`</sup> − `z`, write `v` using `s` − 1 bits if `v` < `m`;
+otherwise, they write the `s` − 1 most significant bits of the `s`-bit
+representation of `v` + `m`, followed by its least significant bit.
+
+All codes assume a 64-bit architecture.
+
+# Examples
+```
+use sux::bits::bit_stream::{BitReader, BitWriter};
+
+let mut writer = BitWriter::default();
+writer.write_gamma(10);
+writer.write_bits(5, 3);
+writer.write_golomb(100, 7);
+let bits = writer.into_bit_vec();
+
+let mut reader = BitReader::new(bits.as_ref());
+assert_eq!(reader.read_gamma(), 10);
+assert_eq!(reader.read_bits(3), 5);
+assert_eq!(reader.read_golomb(7), 100);
+```
+*/
+
+use crate::bits::bit_vec::BitVec;
+
+const BITS: usize = usize::BITS as usize;
+
+/// Return ⌊log₂ `x`⌋ for a positive `x`.
+#[inline(always)]
+fn floor_log2(x: u64) -> usize {
+    63 - x.leading_zeros() as usize
+}
+
+/// Return the number of bits `s` and the threshold `m` of minimal binary
+/// codes in [0..`z`), for `z` > 1.
+#[inline(always)]
+fn minimal_binary_params(z: u128) -> (usize, u128) {
+    let s = 128 - (z - 1).leading_zeros() as usize;
+    (s, (1 << s) - z)
+}
+
+/// Return the interval of the minimal binary code used by ζ<sub>k</sub> for
+/// `x` + 1, with `h` = ⌊⌊log₂(`x` + 1)⌋ / `k`⌋, and its left extreme.
+#[inline(always)]
+fn zeta_interval(h: usize, k: usize) -> (u128, u128) {
+    let left = 1_u128 << (h * k);
+    ((1_u128 << ((h + 1) * k)) - left, left)
+}
+
+/// A writer appending fields and codes to a [`BitVec`].
+///
+/// All writing methods return the number of bits written.
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    data: Vec<usize>,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Create a new writer appending bits to the given bit vector.
+    pub fn new(bits: BitVec<Vec<usize>>) -> Self {
+        let (mut data, len) = bits.into_raw_parts();
+        // Words past the end are zero and can be discarded
+        data.truncate((len + BITS - 1) / BITS);
+        Self { data, len }
+    }
+
+    /// Return the number of bits written so far, including those of the
+    /// bit vector passed at construction time.
+    #[inline(always)]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the bit vector containing the bits written so far.
+    pub fn into_bit_vec(self) -> BitVec<Vec<usize>> {
+        // SAFETY: data contains exactly the words needed by len bits
+        unsafe { BitVec::from_raw_parts(self.data, self.len) }
+    }
+
+    /// Write the `width` lowest bits of `value`.
+    ///
+    /// # Panics
+    /// If `width` is larger than 64, or if `value` does not fit in `width` bits.
+    pub fn write_bits(&mut self, value: u64, width: usize) -> usize {
+        assert!(width <= 64, "The width must be at most 64: {}", width);
+        assert!(
+            width == 64 || value >> width == 0,
+            "Value {} does not fit in {} bits",
+            value,
+            width
+        );
+        if width == 0 {
+            return 0;
+        }
+        let word_index = self.len / BITS;
+        let bit_index = self.len % BITS;
+        if word_index == self.data.len() {
+            self.data.push(0);
+        }
+        self.data[word_index] |= (value as usize) << bit_index;
+        if bit_index + width > BITS {
+            self.data.push((value as usize) >> (BITS - bit_index));
+        }
+        self.len += width;
+        width
+    }
+
+    /// Write `value` in unary code.
+    pub fn write_unary(&mut self, value: u64) -> usize {
+        self.len += value as usize;
+        let word_index = self.len / BITS;
+        if word_index >= self.data.len() {
+            self.data.resize(word_index + 1, 0);
+        }
+        self.data[word_index] |= 1 << (self.len % BITS);
+        self.len += 1;
+        value as usize + 1
+    }
+
+    /// Write `value` in γ code.
+    ///
+    /// # Panics
+    /// If `value` is [`u64::MAX`].
+    pub fn write_gamma(&mut self, value: u64) -> usize {
+        let x = value
+            .checked_add(1)
+            .expect("γ codes cannot represent u64::MAX");
+        let n = floor_log2(x);
+        self.write_unary(n as u64) + self.write_bits(x ^ (1 << n), n)
+    }
+
+    /// Write `value` in δ code.
+    ///
+    /// # Panics
+    /// If `value` is [`u64::MAX`].
+    pub fn write_delta(&mut self, value: u64) -> usize {
+        let x = value
+            .checked_add(1)
+            .expect("δ codes cannot represent u64::MAX");
+        let n = floor_log2(x);
+        self.write_gamma(n as u64) + self.write_bits(x ^ (1 << n), n)
+    }
+
+    /// Write `value` in ζ<sub>`k`</sub> code.
+    ///
+    /// # Panics
+    /// If `k` is not in [1..64], or if `value` is [`u64::MAX`].
+    pub fn write_zeta(&mut self, value: u64, k: usize) -> usize {
+        assert!(
+            (1..=64).contains(&k),
+            "The parameter of ζ codes must be in [1..64]: {}",
+            k
+        );
+        let x = value
+            .checked_add(1)
+            .expect("ζ codes cannot represent u64::MAX");
+        let h = floor_log2(x) / k;
+        let (z, left) = zeta_interval(h, k);
+        self.write_unary(h as u64) + self.write_minimal_binary(x as u128 - left, z)
+    }
+
+    /// Write `value` in Golomb code with modulus `b`.
+    ///
+    /// # Panics
+    /// If `b` is zero.
+    pub fn write_golomb(&mut self, value: u64, b: u64) -> usize {
+        assert!(b > 0, "The modulus of Golomb codes must be positive");
+        self.write_unary(value / b) + self.write_minimal_binary((value % b) as u128, b as u128)
+    }
+
+    /// Write `value` in minimal binary code in [0..`z`).
+    fn write_minimal_binary(&mut self, value: u128, z: u128) -> usize {
+        if z == 1 {
+            return 0;
+        }
+        let (s, m) = minimal_binary_params(z);
+        if value < m {
+            self.write_wide_bits(value, s - 1)
+        } else {
+            let x = value + m;
+            self.write_wide_bits(x >> 1, s - 1) + self.write_bits((x & 1) as u64, 1)
+        }
+    }
+
+    /// Write the `width` lowest bits of `value`, where `width` can be up to
+    /// 128: wider fields are written as two fields, high part first.
+    fn write_wide_bits(&mut self, value: u128, width: usize) -> usize {
+        if width <= 64 {
+            self.write_bits(value as u64, width)
+        } else {
+            self.write_bits((value >> 64) as u64, width - 64) + self.write_bits(value as u64, 64)
+        }
+    }
+}
+
+/// A reader of fields and codes written by a [`BitWriter`].
+///
+/// The reader works on any backend implementing `AsRef<[usize]>`.
+/// Reading past the end of the backend causes a panic; reading past the
+/// end of the bits written, but within the backend, returns
+/// meaningless values.
+#[derive(Debug, Clone)]
+pub struct BitReader<B> {
+    data: B,
+    pos: usize,
+}
+
+impl<B: AsRef<[usize]>> BitReader<B> {
+    /// Create a new reader starting at the beginning of the given words.
+    pub fn new(data: B) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Return the position of the next bit to be read.
+    #[inline(always)]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Move the reader to the given bit position.
+    #[inline(always)]
+    pub fn set_position(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Read a field of `width` bits.
+    ///
+    /// # Panics
+    /// If `width` is larger than 64.
+    pub fn read_bits(&mut self, width: usize) -> u64 {
+        assert!(width <= 64, "The width must be at most 64: {}", width);
+        if width == 0 {
+            return 0;
+        }
+        let words = self.data.as_ref();
+        let word_index = self.pos / BITS;
+        let bit_index = self.pos % BITS;
+        let mut value = words[word_index] >> bit_index;
+        if bit_index + width > BITS {
+            value |= words[word_index + 1] << (BITS - bit_index);
+        }
+        self.pos += width;
+        (value as u64) & (u64::MAX >> (64 - width))
+    }
+
+    /// Read a value in unary code.
+    pub fn read_unary(&mut self) -> u64 {
+        let words = self.data.as_ref();
+        let mut word_index = self.pos / BITS;
+        let mut word = words[word_index] >> (self.pos % BITS);
+        let mut value = 0;
+        if word == 0 {
+            value = BITS - self.pos % BITS;
+            loop {
+                word_index += 1;
+                word = words[word_index];
+                if word != 0 {
+                    break;
+                }
+                value += BITS;
+            }
+        }
+        value += word.trailing_zeros() as usize;
+        self.pos += value + 1;
+        value as u64
+    }
+
+    /// Read a value in γ code.
+    pub fn read_gamma(&mut self) -> u64 {
+        let n = self.read_unary() as usize;
+        ((1 << n) | self.read_bits(n)) - 1
+    }
+
+    /// Read a value in δ code.
+    pub fn read_delta(&mut self) -> u64 {
+        let n = self.read_gamma() as usize;
+        ((1 << n) | self.read_bits(n)) - 1
+    }
+
+    /// Read a value in ζ<sub>`k`</sub> code.
+    ///
+    /// # Panics
+    /// If `k` is not in [1..64].
+    pub fn read_zeta(&mut self, k: usize) -> u64 {
+        assert!(
+            (1..=64).contains(&k),
+            "The parameter of ζ codes must be in [1..64]: {}",
+            k
+        );
+        let h = self.read_unary() as usize;
+        let (z, left) = zeta_interval(h, k);
+        (self.read_minimal_binary(z) + left - 1) as u64
+    }
+
+    /// Read a value in Golomb code with modulus `b`.
+    ///
+    /// # Panics
+    /// If `b` is zero.
+    pub fn read_golomb(&mut self, b: u64) -> u64 {
+        assert!(b > 0, "The modulus of Golomb codes must be positive");
+        self.read_unary() * b + self.read_minimal_binary(b as u128) as u64
+    }
+
+    /// Read a value in minimal binary code in [0..`z`).
+    fn read_minimal_binary(&mut self, z: u128) -> u128 {
+        if z == 1 {
+            return 0;
+        }
+        let (s, m) = minimal_binary_params(z);
+        let x = self.read_wide_bits(s - 1);
+        if x < m {
+            x
+        } else {
+            ((x << 1) | self.read_bits(1) as u128) - m
+        }
+    }
+
+    /// Read a field of `width` bits written by [`BitWriter::write_wide_bits`].
+    fn read_wide_bits(&mut self, width: usize) -> u128 {
+        if width <= 64 {
+            self.read_bits(width) as u128
+        } else {
+            let high = self.read_bits(width - 64) as u128;
+            high << 64 | self.read_bits(64) as u128
+        }
+    }
+}
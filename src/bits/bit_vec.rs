@@ -8,7 +8,7 @@
 
 Bit vector implementations. There are three flavors:
 
-- `BitVec<Vec<usize>>`: a mutable bit vector with a `Vec<usize>` as underlying storage;
+- `BitVec<Vec<usize>>`: a mutable, growable bit vector with a `Vec<usize>` as underlying storage;
 - `BitVec<&[usize]>`: a mutable bit vector with a `&[usize]` as underlying storage,
    mainly useful for [`epserde`];
 - `BitVec<Vec<AtomicUsize>>`: a thread-safe mutable bit vector
@@ -36,7 +36,7 @@ const BITS: usize = usize::BITS as usize;
 ///
 /// In the second case, [`BitVec::get`]
 /// and [`BitVec::set`] are both thread-safe, as they both take an immutable reference.
-#[derive(Epserde, Debug)]
+#[derive(Epserde, Debug, Clone)]
 pub struct BitVec<B = Vec<usize>> {
     data: B,
     len: usize,
@@ -79,6 +79,89 @@ impl BitVec<Vec<usize>> {
     }
 }
 
+/// Growable bit vectors. Bits past the end of the bit vector are
+/// kept at zero, so counts of ones remain correct.
+impl BitVec<Vec<usize>> {
+    /// Create a new empty bit vector with space for at least
+    /// `capacity` bits.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity((capacity + BITS - 1) / BITS),
+            len: 0,
+        }
+    }
+
+    /// Append a bit to the end of this bit vector.
+    pub fn push(&mut self, value: bool) {
+        let word_index = self.len / BITS;
+        if word_index == self.data.len() {
+            self.data.push(0);
+        }
+        self.data[word_index] |= (value as usize) << (self.len % BITS);
+        self.len += 1;
+    }
+
+    /// Remove the last bit of this bit vector and return it, or `None`
+    /// if the bit vector is empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let word_index = self.len / BITS;
+        let bit_index = self.len % BITS;
+        let value = (self.data[word_index] >> bit_index) & 1 != 0;
+        self.data[word_index] &= !(1 << bit_index);
+        Some(value)
+    }
+
+    /// Resize this bit vector in place so that its length is `new_len`,
+    /// filling new positions, if any, with `value`.
+    pub fn resize(&mut self, new_len: usize, value: bool) {
+        let num_words = (new_len + BITS - 1) / BITS;
+        if new_len > self.len {
+            if self.data.len() < num_words {
+                self.data.resize(num_words, 0);
+            }
+            if value {
+                let word_index = self.len / BITS;
+                self.data[word_index] |= usize::MAX << (self.len % BITS);
+                self.data[word_index + 1..num_words].fill(usize::MAX);
+            }
+        } else {
+            self.data.truncate(num_words);
+        }
+        self.len = new_len;
+        self.clear_tail();
+    }
+
+    /// Append the content of `other` to this bit vector.
+    pub fn append<B: AsRef<[usize]>>(&mut self, other: &BitVec<B>) {
+        let other_words = &other.data.as_ref()[..(other.len + BITS - 1) / BITS];
+        // Words past the end are zero and can be discarded
+        self.data.truncate((self.len + BITS - 1) / BITS);
+        let shift = self.len % BITS;
+        if shift == 0 {
+            self.data.extend_from_slice(other_words);
+        } else {
+            for &word in other_words {
+                *self.data.last_mut().unwrap() |= word << shift;
+                self.data.push(word >> (BITS - shift));
+            }
+        }
+        self.len += other.len;
+        self.data.truncate((self.len + BITS - 1) / BITS);
+    }
+}
+
+impl Extend<bool> for BitVec<Vec<usize>> {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
 impl BitVec<Vec<AtomicUsize>> {
     /// Create a new atomic bit vector of length `len`.
     pub fn new_atomic(len: usize) -> Self {
@@ -208,6 +291,177 @@ impl BitVec<Vec<usize>> {
     }
 }
 
+/// Generate a bulk binary operation between bit vectors of the same
+/// length, in place and producing a new vector, together with the
+/// corresponding parallel variants.
+macro_rules! bulk_op {
+    ($name:ident, $name_in_place:ident, $par_name:ident, $par_name_in_place:ident, $desc:literal, $op:expr) => {
+        #[doc = concat!("Compute the ", $desc, " of this bit vector and `other` in place.")]
+        ///
+        /// # Panics
+        /// If the two bit vectors have different lengths.
+        pub fn $name_in_place<B: AsRef<[usize]>>(&mut self, other: &BitVec<B>) {
+            self.apply_in_place(other, $op);
+        }
+
+        #[doc = concat!("Return the ", $desc, " of this bit vector and `other`.")]
+        ///
+        /// # Panics
+        /// If the two bit vectors have different lengths.
+        pub fn $name<B: AsRef<[usize]>>(&self, other: &BitVec<B>) -> Self {
+            let mut result = self.clone();
+            result.$name_in_place(other);
+            result
+        }
+
+        #[doc = concat!("Compute in parallel the ", $desc, " of this bit vector and `other` in place.")]
+        ///
+        /// # Panics
+        /// If the two bit vectors have different lengths.
+        #[cfg(feature = "rayon")]
+        pub fn $par_name_in_place<B: AsRef<[usize]>>(&mut self, other: &BitVec<B>) {
+            self.par_apply_in_place(other, $op);
+        }
+
+        #[doc = concat!("Return the ", $desc, " of this bit vector and `other`, computed in parallel.")]
+        ///
+        /// # Panics
+        /// If the two bit vectors have different lengths.
+        #[cfg(feature = "rayon")]
+        pub fn $par_name<B: AsRef<[usize]>>(&self, other: &BitVec<B>) -> Self {
+            let mut result = self.clone();
+            result.$par_name_in_place(other);
+            result
+        }
+    };
+}
+
+/// Bulk operations work a word at a time. Bits past the end
+/// of the bit vector are guaranteed to remain zero.
+impl BitVec<Vec<usize>> {
+    /// Return the number of words used by this bit vector.
+    #[inline(always)]
+    fn num_words(&self) -> usize {
+        (self.len + BITS - 1) / BITS
+    }
+
+    /// Replace each word of this bit vector with the result of `op`
+    /// applied to it and to the corresponding word of `other`.
+    #[inline(always)]
+    fn apply_in_place<B: AsRef<[usize]>>(
+        &mut self,
+        other: &BitVec<B>,
+        op: fn(usize, usize) -> usize,
+    ) {
+        assert_eq!(
+            self.len, other.len,
+            "The bit vectors have different lengths"
+        );
+        let num_words = self.num_words();
+        for (a, &b) in self.data[..num_words]
+            .iter_mut()
+            .zip(&other.data.as_ref()[..num_words])
+        {
+            *a = op(*a, b);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline(always)]
+    fn par_apply_in_place<B: AsRef<[usize]>>(
+        &mut self,
+        other: &BitVec<B>,
+        op: fn(usize, usize) -> usize,
+    ) {
+        assert_eq!(
+            self.len, other.len,
+            "The bit vectors have different lengths"
+        );
+        let num_words = self.num_words();
+        self.data[..num_words]
+            .par_iter_mut()
+            .zip(other.data.as_ref()[..num_words].par_iter())
+            .for_each(|(a, &b)| *a = op(*a, b));
+    }
+
+    /// Clear the bits of the last word past the end of the bit vector.
+    #[inline(always)]
+    fn clear_tail(&mut self) {
+        let bits = self.len % BITS;
+        if bits != 0 {
+            let last = self.len / BITS;
+            self.data[last] &= (1 << bits) - 1;
+        }
+    }
+
+    bulk_op!(
+        and,
+        and_in_place,
+        par_and,
+        par_and_in_place,
+        "bitwise and",
+        |a, b| a & b
+    );
+    bulk_op!(
+        or,
+        or_in_place,
+        par_or,
+        par_or_in_place,
+        "bitwise or",
+        |a, b| a | b
+    );
+    bulk_op!(
+        xor,
+        xor_in_place,
+        par_xor,
+        par_xor_in_place,
+        "bitwise exclusive or",
+        |a, b| a ^ b
+    );
+    bulk_op!(
+        and_not,
+        and_not_in_place,
+        par_and_not,
+        par_and_not_in_place,
+        "bitwise and with the complement",
+        |a, b| a & !b
+    );
+
+    /// Complement this bit vector in place.
+    pub fn not_in_place(&mut self) {
+        let num_words = self.num_words();
+        for word in &mut self.data[..num_words] {
+            *word = !*word;
+        }
+        self.clear_tail();
+    }
+
+    /// Return the complement of this bit vector.
+    pub fn not(&self) -> Self {
+        let mut result = self.clone();
+        result.not_in_place();
+        result
+    }
+
+    /// Complement this bit vector in place, in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_not_in_place(&mut self) {
+        let num_words = self.num_words();
+        self.data[..num_words]
+            .par_iter_mut()
+            .for_each(|word| *word = !*word);
+        self.clear_tail();
+    }
+
+    /// Return the complement of this bit vector, computed in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_not(&self) -> Self {
+        let mut result = self.clone();
+        result.par_not_in_place();
+        result
+    }
+}
+
 impl BitVec<&[usize]> {
     pub fn get(&self, index: usize) -> bool {
         panic_if_out_of_bounds!(index, self.len);
@@ -253,6 +507,53 @@ impl BitVec<Vec<AtomicUsize>> {
                 .fetch_and(!(1 << bit_index), order);
         }
     }
+
+    /// Set a bit to one, returning its previous value.
+    ///
+    /// This is an atomic test-and-set: if several threads set the same bit
+    /// concurrently, exactly one of them will see `false`, so it can be used
+    /// to claim bits exactly once (e.g., in a concurrent visit).
+    pub fn fetch_set(&self, index: usize, order: Ordering) -> bool {
+        panic_if_out_of_bounds!(index, self.len);
+        let mask = 1 << (index % BITS);
+        self.data[index / BITS].fetch_or(mask, order) & mask != 0
+    }
+
+    /// Set a bit to zero, returning its previous value.
+    pub fn fetch_clear(&self, index: usize, order: Ordering) -> bool {
+        panic_if_out_of_bounds!(index, self.len);
+        let mask = 1 << (index % BITS);
+        self.data[index / BITS].fetch_and(!mask, order) & mask != 0
+    }
+
+    /// Complement a bit, returning its previous value.
+    pub fn fetch_toggle(&self, index: usize, order: Ordering) -> bool {
+        panic_if_out_of_bounds!(index, self.len);
+        let mask = 1 << (index % BITS);
+        self.data[index / BITS].fetch_xor(mask, order) & mask != 0
+    }
+
+    /// Set to one the bits of the word of given index that are set in `mask`,
+    /// returning the previous value of the word.
+    ///
+    /// The word of index `i` contains the bits of index
+    /// [`i * usize::BITS`..`(i + 1) * usize::BITS`), starting from the lowest bit.
+    ///
+    /// # Panics
+    /// If `mask` contains bits past the end of the bit vector.
+    pub fn fetch_or_word(&self, word_index: usize, mask: usize, order: Ordering) -> usize {
+        let end = word_index
+            .checked_mul(BITS)
+            .and_then(|start| start.checked_add(BITS - mask.leading_zeros() as usize))
+            .unwrap_or(usize::MAX);
+        if mask != 0 && end > self.len {
+            panic!(
+                "Mask {:#x} for word {} sets bits past the end: {} > {}",
+                mask, word_index, end, self.len
+            );
+        }
+        self.data[word_index].fetch_or(mask, order)
+    }
 }
 
 /// An immutable bit vector that returns the number of ones.
@@ -547,3 +848,210 @@ impl<B: AsRef<[usize]>> Iterator for BitVecOnesIterator<B> {
         Some(res)
     }
 }
+
+/// Generate the iteration methods of a bit vector type whose backend
+/// implements `AsRef<[usize]>`.
+macro_rules! impl_iterators {
+    ($ty:ident) => {
+        impl<B: AsRef<[usize]>> $ty<B> {
+            /// Return an iterator over the positions of the ones.
+            pub fn iter_ones(&self) -> OnesIterator<'_> {
+                self.iter_ones_from(0)
+            }
+
+            /// Return an iterator over the positions of the ones
+            /// starting from position `pos` (included).
+            pub fn iter_ones_from(&self, pos: usize) -> OnesIterator<'_> {
+                OnesIterator::new(self.data.as_ref(), self.len, pos)
+            }
+
+            /// Return an iterator over the positions of the zeros.
+            pub fn iter_zeros(&self) -> ZerosIterator<'_> {
+                self.iter_zeros_from(0)
+            }
+
+            /// Return an iterator over the positions of the zeros
+            /// starting from position `pos` (included).
+            pub fn iter_zeros_from(&self, pos: usize) -> ZerosIterator<'_> {
+                ZerosIterator::new(self.data.as_ref(), self.len, pos)
+            }
+
+            /// Return an iterator over the positions of the ones
+            /// in decreasing order.
+            pub fn iter_ones_rev(&self) -> OnesRevIterator<'_> {
+                self.iter_ones_rev_from(self.len)
+            }
+
+            /// Return an iterator over the positions of the ones smaller
+            /// than `pos` in decreasing order.
+            pub fn iter_ones_rev_from(&self, pos: usize) -> OnesRevIterator<'_> {
+                OnesRevIterator::new(self.data.as_ref(), pos.min(self.len))
+            }
+
+            /// Return an iterator over the maximal runs of ones, returned
+            /// as ranges of positions.
+            pub fn runs(&self) -> RunsIterator<'_> {
+                self.runs_from(0)
+            }
+
+            /// Return an iterator over the maximal runs of ones
+            /// starting from position `pos` (included); a run containing
+            /// `pos` is returned starting from `pos`.
+            pub fn runs_from(&self, pos: usize) -> RunsIterator<'_> {
+                RunsIterator {
+                    words: self.data.as_ref(),
+                    len: self.len,
+                    pos,
+                }
+            }
+        }
+    };
+}
+
+impl_iterators!(BitVec);
+impl_iterators!(CountBitVec);
+
+/// An iterator over the positions of the ones (if `ONES` is true) or
+/// of the zeros (otherwise) of a bit vector, scanning a word at a time.
+#[derive(Debug, Clone)]
+pub struct BitIterator<'a, const ONES: bool> {
+    words: &'a [usize],
+    len: usize,
+    num_words: usize,
+    word_index: usize,
+    /// The current word, with the bits already returned cleared.
+    word: usize,
+}
+
+/// An iterator over the positions of the ones of a bit vector.
+pub type OnesIterator<'a> = BitIterator<'a, true>;
+/// An iterator over the positions of the zeros of a bit vector.
+pub type ZerosIterator<'a> = BitIterator<'a, false>;
+
+impl<'a, const ONES: bool> BitIterator<'a, ONES> {
+    fn new(words: &'a [usize], len: usize, pos: usize) -> Self {
+        let num_words = (len + BITS - 1) / BITS;
+        if pos >= len {
+            return Self {
+                words,
+                len,
+                num_words,
+                word_index: num_words,
+                word: 0,
+            };
+        }
+        let word_index = pos / BITS;
+        Self {
+            words,
+            len,
+            num_words,
+            word_index,
+            word: Self::word(words, word_index) & (usize::MAX << (pos % BITS)),
+        }
+    }
+
+    #[inline(always)]
+    fn word(words: &[usize], word_index: usize) -> usize {
+        if ONES {
+            words[word_index]
+        } else {
+            !words[word_index]
+        }
+    }
+}
+
+impl<'a, const ONES: bool> Iterator for BitIterator<'a, ONES> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            if self.word_index + 1 >= self.num_words {
+                return None;
+            }
+            self.word_index += 1;
+            self.word = Self::word(self.words, self.word_index);
+        }
+        let pos = self.word_index * BITS + self.word.trailing_zeros() as usize;
+        // Complemented words have ones past the end
+        if pos >= self.len {
+            self.word = 0;
+            return None;
+        }
+        self.word &= self.word - 1;
+        Some(pos)
+    }
+}
+
+impl<'a, const ONES: bool> core::iter::FusedIterator for BitIterator<'a, ONES> {}
+
+/// An iterator over the positions of the ones of a bit vector in
+/// decreasing order, scanning a word at a time.
+#[derive(Debug, Clone)]
+pub struct OnesRevIterator<'a> {
+    words: &'a [usize],
+    word_index: usize,
+    /// The current word, with the bits already returned cleared.
+    word: usize,
+}
+
+impl<'a> OnesRevIterator<'a> {
+    fn new(words: &'a [usize], pos: usize) -> Self {
+        if pos == 0 {
+            return Self {
+                words,
+                word_index: 0,
+                word: 0,
+            };
+        }
+        let word_index = (pos - 1) / BITS;
+        let bits = pos - word_index * BITS;
+        Self {
+            words,
+            word_index,
+            word: words[word_index] & (usize::MAX >> (BITS - bits)),
+        }
+    }
+}
+
+impl<'a> Iterator for OnesRevIterator<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            if self.word_index == 0 {
+                return None;
+            }
+            self.word_index -= 1;
+            self.word = self.words[self.word_index];
+        }
+        let bit = BITS - 1 - self.word.leading_zeros() as usize;
+        self.word &= !(1 << bit);
+        Some(self.word_index * BITS + bit)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for OnesRevIterator<'a> {}
+
+/// An iterator over the maximal runs of ones of a bit vector, returned
+/// as ranges of positions.
+#[derive(Debug, Clone)]
+pub struct RunsIterator<'a> {
+    words: &'a [usize],
+    len: usize,
+    pos: usize,
+}
+
+impl<'a> Iterator for RunsIterator<'a> {
+    type Item = core::ops::Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = OnesIterator::new(self.words, self.len, self.pos).next()?;
+        let end = ZerosIterator::new(self.words, self.len, start)
+            .next()
+            .unwrap_or(self.len);
+        self.pos = end;
+        Some(start..end)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for RunsIterator<'a> {}
@@ -0,0 +1,641 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Balanced parentheses with navigation based on a range min tree.
+
+*/
+
+use crate::bits::bit_vec::BitVec;
+use crate::traits::prelude::*;
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+/// The number of words in a block.
+const WORDS_PER_BLOCK: usize = 8;
+/// The number of bits in a block.
+const BLOCK_BITS: usize = WORDS_PER_BLOCK * BITS;
+
+/// The excess of each byte, that is, the number of ones minus the
+/// number of zeros.
+const BYTE_EXCESS: [i8; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        table[byte] = 2 * (byte as u8).count_ones() as i8 - 8;
+        byte += 1;
+    }
+    table
+};
+
+/// The minimum excess of each byte after reading 1 to 8 of its bits
+/// (used in forward scans).
+const BYTE_MIN_AFTER: [i8; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let (mut excess, mut min) = (0, i8::MAX);
+        let mut bit = 0;
+        while bit < 8 {
+            excess += if byte & (1 << bit) != 0 { 1 } else { -1 };
+            if excess < min {
+                min = excess;
+            }
+            bit += 1;
+        }
+        table[byte] = min;
+        byte += 1;
+    }
+    table
+};
+
+/// The minimum excess of each byte after reading 0 to 7 of its bits
+/// (used in backward scans).
+const BYTE_MIN_BEFORE: [i8; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let (mut excess, mut min) = (0, 0);
+        let mut bit = 0;
+        while bit < 7 {
+            excess += if byte & (1 << bit) != 0 { 1 } else { -1 };
+            if excess < min {
+                min = excess;
+            }
+            bit += 1;
+        }
+        table[byte] = min;
+        byte += 1;
+    }
+    table
+};
+
+/// Return the excess of a word.
+#[inline(always)]
+fn word_excess(word: usize) -> i64 {
+    2 * word.count_ones() as i64 - BITS as i64
+}
+
+/// Return the minimum over the bytes of a word of the excess before the byte
+/// plus the value of `table` for the byte: with [`BYTE_MIN_AFTER`] this is
+/// the minimum excess after reading some nonempty prefix of the word, and
+/// with [`BYTE_MIN_BEFORE`] after reading some proper prefix.
+#[inline(always)]
+fn word_min(word: usize, table: &[i8; 256]) -> i64 {
+    let (mut excess, mut min) = (0, i64::MAX);
+    for i in 0..BITS / 8 {
+        let byte = (word >> (8 * i)) & 0xFF;
+        min = min.min(excess + table[byte] as i64);
+        excess += BYTE_EXCESS[byte] as i64;
+    }
+    min
+}
+
+/// A sequence of parentheses supporting navigation primitives.
+///
+/// Ones represent open parentheses and zeros represent closed
+/// parentheses. The excess at a position is the number of open parentheses
+/// minus the number of closed parentheses preceding the position;
+/// in this implementation, it might be negative, so the sequence need not be
+/// balanced, but [`find_close`](BalancedParens::find_close),
+/// [`find_open`](BalancedParens::find_open) and
+/// [`enclose`](BalancedParens::enclose) have their usual meaning only on
+/// balanced sequences.
+///
+/// All primitives reduce to searching forward or backward for the first
+/// position with an excess not larger than a target. The sequence is divided
+/// in blocks of 512 bits; we store the excess at the start of each block and
+/// a complete binary tree containing, for each node, the minimum excess of
+/// the positions in the blocks it covers. Searches scan the block of the
+/// starting position, climb the tree to find the nearest block containing the
+/// target, and scan that block. Scans skip whole words whose minimum excess
+/// is above the target, using the popcount of the word for its excess, and
+/// otherwise proceed a byte at a time using tables of byte excesses and
+/// minimum excesses.
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// // (()(()))
+/// let mut bits = BitVec::new(0);
+/// bits.extend([true, true, false, true, true, false, false, false]);
+/// let bp = BalancedParens::new(bits);
+///
+/// assert_eq!(bp.find_close(0), Some(7));
+/// assert_eq!(bp.find_close(3), Some(6));
+/// assert_eq!(bp.find_open(5), Some(4));
+/// assert_eq!(bp.enclose(4), Some(3));
+/// assert_eq!(bp.enclose(0), None);
+/// assert_eq!(bp.excess(4), 2);
+/// assert_eq!(bp.rmq(2, 6), 3);
+/// ```
+///
+/// # Reference:
+/// - [Gonzalo Navarro and Kunihiko Sadakane, Fully Functional Static and Dynamic Succinct Trees](https://doi.org/10.1145/2601073)
+#[derive(Epserde, Debug, Clone)]
+pub struct BalancedParens<B = BitVec, E = Vec<i64>> {
+    bits: B,
+    /// The excess at the start of each block.
+    block_excess: E,
+    /// A complete binary tree stored in heap order starting at index 1,
+    /// whose leaves contain the minimum excess of each block.
+    tree: E,
+}
+
+impl<B, E> BalancedParens<B, E> {
+    /// # Safety
+    /// `block_excess` and `tree` must be those of the bit vector `bits`.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(bits: B, block_excess: E, tree: E) -> Self {
+        Self {
+            bits,
+            block_excess,
+            tree,
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> (B, E, E) {
+        (self.bits, self.block_excess, self.tree)
+    }
+}
+
+impl<B: BitLength + AsRef<[usize]>> BalancedParens<B> {
+    /// Build the structure on the given bit vector.
+    ///
+    /// Bits of the underlying storage after the [length](BitLength::len)
+    /// of the bit vector must be zero.
+    pub fn new(bits: B) -> Self {
+        let len = bits.len();
+        let words = bits.as_ref();
+        let num_blocks = (len + BLOCK_BITS - 1) / BLOCK_BITS;
+        let num_leaves = num_blocks.next_power_of_two();
+        let mut block_excess = Vec::with_capacity(num_blocks);
+        let mut tree = vec![i64::MAX; 2 * num_leaves];
+
+        let mut excess = 0;
+        for block in 0..num_blocks {
+            block_excess.push(excess);
+            let mut min = excess;
+            for pos in block * BLOCK_BITS..len.min((block + 1) * BLOCK_BITS) {
+                // The excess at pos is included in the minimum
+                min = min.min(excess);
+                excess += if words[pos / BITS] >> (pos % BITS) & 1 != 0 {
+                    1
+                } else {
+                    -1
+                };
+            }
+            tree[num_leaves + block] = min;
+        }
+        for node in (1..num_leaves).rev() {
+            tree[node] = tree[2 * node].min(tree[2 * node + 1]);
+        }
+
+        Self {
+            bits,
+            block_excess,
+            tree,
+        }
+    }
+}
+
+impl<B: BitLength + AsRef<[usize]>, E: AsRef<[i64]>> BalancedParens<B, E> {
+    #[inline(always)]
+    fn num_leaves(&self) -> usize {
+        self.tree.as_ref().len() / 2
+    }
+
+    /// Return the bit at the given position.
+    #[inline(always)]
+    fn bit(&self, pos: usize) -> bool {
+        self.bits.as_ref()[pos / BITS] >> (pos % BITS) & 1 != 0
+    }
+
+    /// Return the byte starting at the given position, which must
+    /// be a multiple of 8.
+    #[inline(always)]
+    fn byte(&self, pos: usize) -> usize {
+        (self.bits.as_ref()[pos / BITS] >> (pos % BITS)) & 0xFF
+    }
+
+    /// Return whether the parenthesis at the given position is open.
+    ///
+    /// # Panics
+    /// If `pos` is not smaller than the length of the sequence.
+    pub fn is_open(&self, pos: usize) -> bool {
+        assert!(pos < self.bits.len(), "Position out of bounds: {}", pos);
+        self.bit(pos)
+    }
+
+    /// Return the excess at the given position, that is, the number of open
+    /// parentheses minus the number of closed parentheses before `pos`.
+    ///
+    /// # Panics
+    /// If `pos` is larger than the length of the sequence.
+    pub fn excess(&self, pos: usize) -> i64 {
+        let len = self.bits.len();
+        assert!(pos <= len, "Position out of bounds: {} > {}", pos, len);
+        let block = pos / BLOCK_BITS;
+        if block == self.block_excess.as_ref().len() {
+            // The end of a sequence whose length is a multiple of the block size
+            return if block == 0 {
+                0
+            } else {
+                self.excess_at_block_end(block - 1)
+            };
+        }
+        let words = self.bits.as_ref();
+        let first_word = block * WORDS_PER_BLOCK;
+        let word = pos / BITS;
+        let mut ones = words[first_word..word]
+            .iter()
+            .map(|w| w.count_ones() as i64)
+            .sum::<i64>();
+        if pos % BITS != 0 {
+            ones += (words[word] & ((1 << (pos % BITS)) - 1)).count_ones() as i64;
+        }
+        self.block_excess.as_ref()[block] + 2 * ones - (pos - block * BLOCK_BITS) as i64
+    }
+
+    /// Return the excess at the end of the given block.
+    fn excess_at_block_end(&self, block: usize) -> i64 {
+        let words = self.bits.as_ref();
+        let len = self.bits.len();
+        let end = len.min((block + 1) * BLOCK_BITS);
+        let ones = words[block * WORDS_PER_BLOCK..(end + BITS - 1) / BITS]
+            .iter()
+            .map(|w| w.count_ones() as i64)
+            .sum::<i64>();
+        self.block_excess.as_ref()[block] + 2 * ones - (end - block * BLOCK_BITS) as i64
+    }
+
+    /// Scan forward the positions in (`pos`..`end`], given the excess at `pos`,
+    /// returning the first position with excess at most `target`.
+    fn fwd_scan(&self, mut pos: usize, end: usize, mut excess: i64, target: i64) -> Option<usize> {
+        let words = self.bits.as_ref();
+        while pos < end {
+            if pos % BITS == 0 && pos + BITS <= end {
+                let word = words[pos / BITS];
+                if excess + word_min(word, &BYTE_MIN_AFTER) > target {
+                    excess += word_excess(word);
+                    pos += BITS;
+                    continue;
+                }
+            }
+            if pos % 8 == 0 && pos + 8 <= end {
+                let byte = self.byte(pos);
+                if excess + (BYTE_MIN_AFTER[byte] as i64) > target {
+                    excess += BYTE_EXCESS[byte] as i64;
+                    pos += 8;
+                    continue;
+                }
+            }
+            excess += if self.bit(pos) { 1 } else { -1 };
+            pos += 1;
+            if excess <= target {
+                return Some(pos);
+            }
+        }
+        None
+    }
+
+    /// Scan backward the positions in [`start`..`pos`), given the excess at
+    /// `pos`, returning the last position with excess at most `target`.
+    fn bwd_scan(
+        &self,
+        mut pos: usize,
+        start: usize,
+        mut excess: i64,
+        target: i64,
+    ) -> Option<usize> {
+        let words = self.bits.as_ref();
+        while pos > start {
+            if pos % BITS == 0 && pos - BITS >= start {
+                let word = words[pos / BITS - 1];
+                let excess_before = excess - word_excess(word);
+                if excess_before + word_min(word, &BYTE_MIN_BEFORE) > target {
+                    excess = excess_before;
+                    pos -= BITS;
+                    continue;
+                }
+            }
+            if pos % 8 == 0 && pos - 8 >= start {
+                let byte = self.byte(pos - 8);
+                let excess_before = excess - BYTE_EXCESS[byte] as i64;
+                if excess_before + (BYTE_MIN_BEFORE[byte] as i64) > target {
+                    excess = excess_before;
+                    pos -= 8;
+                    continue;
+                }
+            }
+            pos -= 1;
+            excess -= if self.bit(pos) { 1 } else { -1 };
+            if excess <= target {
+                return Some(pos);
+            }
+        }
+        None
+    }
+
+    /// Return the minimum excess of the positions in [`pos`..`end`],
+    /// given the excess at `pos`.
+    fn min_scan(&self, mut pos: usize, end: usize, mut excess: i64) -> i64 {
+        let words = self.bits.as_ref();
+        let mut min = excess;
+        while pos < end {
+            if pos % BITS == 0 && pos + BITS <= end {
+                let word = words[pos / BITS];
+                min = min.min(excess + word_min(word, &BYTE_MIN_AFTER));
+                excess += word_excess(word);
+                pos += BITS;
+            } else if pos % 8 == 0 && pos + 8 <= end {
+                let byte = self.byte(pos);
+                min = min.min(excess + BYTE_MIN_AFTER[byte] as i64);
+                excess += BYTE_EXCESS[byte] as i64;
+                pos += 8;
+            } else {
+                excess += if self.bit(pos) { 1 } else { -1 };
+                min = min.min(excess);
+                pos += 1;
+            }
+        }
+        min
+    }
+
+    /// Return the first block after `block` containing a position
+    /// with excess at most `target`.
+    fn next_block(&self, block: usize, target: i64) -> Option<usize> {
+        let tree = self.tree.as_ref();
+        let num_leaves = self.num_leaves();
+        let mut node = num_leaves + block;
+        loop {
+            if node == 1 {
+                return None;
+            }
+            if node % 2 == 0 && tree[node + 1] <= target {
+                node += 1;
+                break;
+            }
+            node /= 2;
+        }
+        while node < num_leaves {
+            node = if tree[2 * node] <= target {
+                2 * node
+            } else {
+                2 * node + 1
+            };
+        }
+        Some(node - num_leaves)
+    }
+
+    /// Return the last block before `block` containing a position
+    /// with excess at most `target`.
+    fn prev_block(&self, block: usize, target: i64) -> Option<usize> {
+        let tree = self.tree.as_ref();
+        let num_leaves = self.num_leaves();
+        let mut node = num_leaves + block;
+        loop {
+            if node == 1 {
+                return None;
+            }
+            if node % 2 == 1 && tree[node - 1] <= target {
+                node -= 1;
+                break;
+            }
+            node /= 2;
+        }
+        while node < num_leaves {
+            node = if tree[2 * node + 1] <= target {
+                2 * node + 1
+            } else {
+                2 * node
+            };
+        }
+        Some(node - num_leaves)
+    }
+
+    /// Return the first position after `pos` with excess at most `target`,
+    /// or `None` if there is no such position.
+    ///
+    /// If `target` is smaller than the excess at `pos`, the position returned
+    /// is the first one with excess exactly `target`.
+    ///
+    /// # Panics
+    /// If `pos` is larger than the length of the sequence.
+    pub fn fwd_search(&self, pos: usize, target: i64) -> Option<usize> {
+        let len = self.bits.len();
+        assert!(pos <= len, "Position out of bounds: {} > {}", pos, len);
+        if pos == len {
+            return None;
+        }
+        let block = pos / BLOCK_BITS;
+        let end = len.min((block + 1) * BLOCK_BITS);
+        if let Some(pos) = self.fwd_scan(pos, end, self.excess(pos), target) {
+            return Some(pos);
+        }
+        match self.next_block(block, target) {
+            Some(next) => {
+                let start = next * BLOCK_BITS;
+                let excess = self.block_excess.as_ref()[next];
+                if excess <= target {
+                    return Some(start);
+                }
+                self.fwd_scan(start, len.min(start + BLOCK_BITS), excess, target)
+            }
+            // The blocks do not contain the end of the sequence
+            None if end < len && self.excess(len) <= target => Some(len),
+            None => None,
+        }
+    }
+
+    /// Return the last position before `pos` with excess at most `target`,
+    /// or `None` if there is no such position.
+    ///
+    /// If `target` is smaller than the excess at `pos`, the position returned
+    /// is the last one with excess exactly `target`.
+    ///
+    /// # Panics
+    /// If `pos` is larger than the length of the sequence.
+    pub fn bwd_search(&self, pos: usize, target: i64) -> Option<usize> {
+        let len = self.bits.len();
+        assert!(pos <= len, "Position out of bounds: {} > {}", pos, len);
+        if pos == 0 {
+            return None;
+        }
+        let block = (pos - 1) / BLOCK_BITS;
+        let start = block * BLOCK_BITS;
+        if let Some(pos) = self.bwd_scan(pos, start, self.excess(pos), target) {
+            return Some(pos);
+        }
+        let prev = self.prev_block(block, target)?;
+        let end = self.bits.len().min((prev + 1) * BLOCK_BITS);
+        self.bwd_scan(
+            end,
+            prev * BLOCK_BITS,
+            self.excess_at_block_end(prev),
+            target,
+        )
+    }
+
+    /// Return the position of the parenthesis matching the open parenthesis
+    /// at `pos`, or `None` if there is no such parenthesis.
+    ///
+    /// # Panics
+    /// If `pos` is not the position of an open parenthesis.
+    pub fn find_close(&self, pos: usize) -> Option<usize> {
+        assert!(self.is_open(pos), "Not an open parenthesis: {}", pos);
+        self.fwd_search(pos, self.excess(pos)).map(|p| p - 1)
+    }
+
+    /// Return the position of the parenthesis matching the closed parenthesis
+    /// at `pos`, or `None` if there is no such parenthesis.
+    ///
+    /// # Panics
+    /// If `pos` is not the position of a closed parenthesis.
+    pub fn find_open(&self, pos: usize) -> Option<usize> {
+        assert!(!self.is_open(pos), "Not a closed parenthesis: {}", pos);
+        self.bwd_search(pos, self.excess(pos + 1))
+    }
+
+    /// Return the position of the open parenthesis of the tightest pair
+    /// enclosing the open parenthesis at `pos`, or `None` if there is no
+    /// such pair.
+    ///
+    /// # Panics
+    /// If `pos` is not the position of an open parenthesis.
+    pub fn enclose(&self, pos: usize) -> Option<usize> {
+        assert!(self.is_open(pos), "Not an open parenthesis: {}", pos);
+        self.bwd_search(pos, self.excess(pos) - 1)
+    }
+
+    /// Return the leftmost position with minimum excess among the positions
+    /// in \[`start`..`end`\].
+    ///
+    /// # Panics
+    /// If `start` is larger than `end`, or `end` is larger than the
+    /// length of the sequence.
+    pub fn rmq(&self, start: usize, end: usize) -> usize {
+        let len = self.bits.len();
+        assert!(start <= end, "Empty range: {} > {}", start, end);
+        assert!(end <= len, "Position out of bounds: {} > {}", end, len);
+        let first_block = start / BLOCK_BITS;
+        let last_block = end / BLOCK_BITS;
+        let excess = self.excess(start);
+
+        let min = if first_block == last_block {
+            self.min_scan(start, end, excess)
+        } else {
+            let first_end = (first_block + 1) * BLOCK_BITS;
+            let mut min = self.min_scan(start, first_end - 1, excess);
+            if last_block < self.block_excess.as_ref().len() {
+                let last_start = last_block * BLOCK_BITS;
+                min =
+                    min.min(self.min_scan(last_start, end, self.block_excess.as_ref()[last_block]));
+            } else {
+                // The end of a sequence whose length is a multiple of the block size
+                min = min.min(self.excess(len));
+            }
+            // Minimum on the tree over the blocks in between
+            let tree = self.tree.as_ref();
+            let (mut left, mut right) = (
+                self.num_leaves() + first_block,
+                self.num_leaves() + last_block,
+            );
+            // Leaves in (left..right), exclusive
+            while left + 1 < right {
+                if left % 2 == 0 {
+                    min = min.min(tree[left + 1]);
+                }
+                if right % 2 == 1 {
+                    min = min.min(tree[right - 1]);
+                }
+                left /= 2;
+                right /= 2;
+            }
+            min
+        };
+
+        if excess == min {
+            start
+        } else {
+            // There are no smaller excesses in the range, so the first
+            // position with excess at most min has excess min
+            self.fwd_search(start, min).unwrap()
+        }
+    }
+}
+
+impl<B: BitLength, E> BitLength for BalancedParens<B, E> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<B: AsRef<[usize]>, E> AsRef<[usize]> for BalancedParens<B, E> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
+
+impl<B: BitCount, E> BitCount for BalancedParens<B, E> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+/// If the underlying implementation has rank, forward the methods.
+impl<B: Rank, E> Rank for BalancedParens<B, E> {
+    #[inline(always)]
+    fn rank(&self, pos: usize) -> usize {
+        self.bits.rank(pos)
+    }
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_unchecked(pos)
+    }
+}
+
+/// If the underlying implementation has rank zero, forward the methods.
+impl<B: RankZero, E> RankZero for BalancedParens<B, E> {
+    #[inline(always)]
+    fn rank_zero(&self, pos: usize) -> usize {
+        self.bits.rank_zero(pos)
+    }
+    #[inline(always)]
+    unsafe fn rank_zero_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_zero_unchecked(pos)
+    }
+}
+
+/// If the underlying implementation has select, forward the methods.
+impl<B: Select, E> Select for BalancedParens<B, E> {
+    #[inline(always)]
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.bits.select(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_unchecked(rank)
+    }
+}
+
+/// If the underlying implementation has select zero, forward the methods.
+impl<B: SelectZero, E> SelectZero for BalancedParens<B, E> {
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
@@ -5,8 +5,10 @@
  */
 
 use crate::prelude::*;
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
 use epserde::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::sync::atomic::{compiler_fence, fence, AtomicUsize, Ordering};
 
 const BITS: usize = core::mem::size_of::<usize>() * 8;
@@ -51,12 +53,18 @@ fn mask(bit_width: usize) -> usize {
     }
 }
 
+/// Return the number of words used to store `len` values of given bit width.
+///
+/// We need at least one word to handle the case of bit width zero.
+#[inline(always)]
+fn num_words(len: usize, bit_width: usize) -> usize {
+    ((len * bit_width + BITS - 1) / BITS).max(1)
+}
+
 impl CompactArray<Vec<usize>> {
     pub fn new(bit_width: usize, len: usize) -> Self {
-        // We need at least one word to handle the case of bit width zero.
-        let n_of_words = ((len * bit_width + BITS - 1) / BITS).max(1);
         Self {
-            data: vec![0; n_of_words],
+            data: vec![0; num_words(len, bit_width)],
             bit_width,
             mask: mask(bit_width),
             len,
@@ -64,6 +72,300 @@ impl CompactArray<Vec<usize>> {
     }
 }
 
+/// Read a value of given bit width starting at a given bit position.
+#[inline(always)]
+fn read(data: &[usize], pos: usize, bit_width: usize, mask: usize) -> usize {
+    let word_index = pos / BITS;
+    let bit_index = pos % BITS;
+    if bit_index + bit_width <= BITS {
+        (data[word_index] >> bit_index) & mask
+    } else {
+        (data[word_index] >> bit_index | data[word_index + 1] << (BITS - bit_index)) & mask
+    }
+}
+
+/// Write a value of given bit width starting at a given bit position.
+#[inline(always)]
+fn write(data: &mut [usize], pos: usize, bit_width: usize, mask: usize, value: usize) {
+    let word_index = pos / BITS;
+    let bit_index = pos % BITS;
+    data[word_index] = data[word_index] & !(mask << bit_index) | value << bit_index;
+    if bit_index + bit_width > BITS {
+        data[word_index + 1] =
+            data[word_index + 1] & !(mask >> (BITS - bit_index)) | value >> (BITS - bit_index);
+    }
+}
+
+/// Growable compact arrays. The backing vector contains just the words
+/// necessary to store the values (but at least one), and bits past the
+/// last value are kept at zero, so equality is not affected by the
+/// history of the array.
+impl CompactArray<Vec<usize>> {
+    /// Create a new empty compact array with space for at least
+    /// `capacity` values of given bit width.
+    pub fn with_capacity(bit_width: usize, capacity: usize) -> Self {
+        let mut data = Vec::with_capacity(num_words(capacity, bit_width));
+        data.push(0);
+        Self {
+            data,
+            bit_width,
+            mask: mask(bit_width),
+            len: 0,
+        }
+    }
+
+    /// Append a value to the end of this compact array.
+    ///
+    /// # Panics
+    /// If the value does not fit in [`VSliceCore::bit_width`] bits.
+    /// Use [`widen`](CompactArray::widen) to make room for larger values.
+    pub fn push(&mut self, value: usize) {
+        panic_if_value!(value, self.mask, self.bit_width);
+        let num_words = num_words(self.len + 1, self.bit_width);
+        if self.data.len() < num_words {
+            self.data.resize(num_words, 0);
+        }
+        self.len += 1;
+        unsafe { self.set_unchecked(self.len - 1, value) };
+    }
+
+    /// Shorten this compact array to `len` values.
+    ///
+    /// If `len` is greater than the current length, this has no effect.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.len = len;
+        self.data.truncate(num_words(len, self.bit_width));
+        self.clear_tail();
+    }
+
+    /// Rewrite in place the values of this compact array using
+    /// a different bit width.
+    ///
+    /// Return an error if `bit_width` is larger than the number of
+    /// bits in a `usize`, or if some value does not fit in `bit_width` bits;
+    /// in both cases, the array is left unchanged.
+    pub fn repack(&mut self, bit_width: usize) -> Result<()> {
+        ensure!(
+            bit_width <= BITS,
+            "Bit width {} is larger than {}",
+            bit_width,
+            BITS
+        );
+        let new_mask = mask(bit_width);
+        if bit_width < self.bit_width {
+            for index in 0..self.len {
+                let value = unsafe { self.get_unchecked(index) };
+                if value & new_mask != value {
+                    bail!(
+                        "Value {} at index {} does not fit in {} bits",
+                        value,
+                        index,
+                        bit_width
+                    );
+                }
+            }
+        }
+
+        let (old_bit_width, old_mask) = (self.bit_width, self.mask);
+        let num_words = num_words(self.len, bit_width);
+        if bit_width > old_bit_width {
+            // Values move to higher positions, so we proceed backwards
+            self.data.resize(num_words, 0);
+            for index in (0..self.len).rev() {
+                let value = read(&self.data, index * old_bit_width, old_bit_width, old_mask);
+                write(
+                    &mut self.data,
+                    index * bit_width,
+                    bit_width,
+                    new_mask,
+                    value,
+                );
+            }
+        } else if bit_width < old_bit_width {
+            // Values move to lower positions, so we proceed forwards
+            for index in 0..self.len {
+                let value = read(&self.data, index * old_bit_width, old_bit_width, old_mask);
+                write(
+                    &mut self.data,
+                    index * bit_width,
+                    bit_width,
+                    new_mask,
+                    value,
+                );
+            }
+            self.data.truncate(num_words);
+        }
+        self.bit_width = bit_width;
+        self.mask = new_mask;
+        self.clear_tail();
+        Ok(())
+    }
+
+    /// Increase the bit width of this compact array, rewriting its values
+    /// in place.
+    ///
+    /// # Panics
+    /// If `bit_width` is smaller than the current bit width or larger than
+    /// the number of bits in a `usize`.
+    pub fn widen(&mut self, bit_width: usize) {
+        assert!(
+            bit_width >= self.bit_width,
+            "Bit width {} is smaller than the current bit width {}",
+            bit_width,
+            self.bit_width
+        );
+        self.repack(bit_width).unwrap();
+    }
+
+    /// Clear the bits past the last value.
+    fn clear_tail(&mut self) {
+        let used = self.len * self.bit_width;
+        let last = self.data.last_mut().unwrap();
+        if used == 0 {
+            *last = 0;
+        } else if used % BITS != 0 {
+            *last &= usize::MAX >> (BITS - used % BITS);
+        }
+    }
+}
+
+impl Extend<usize> for CompactArray<Vec<usize>> {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// Return the minimum bit width of a value, that is, the number of
+/// bits up to the most significant one.
+///
+/// Applied to the bitwise or of a sequence of values, it returns the
+/// minimum bit width of the sequence.
+#[inline(always)]
+fn min_bit_width(value: usize) -> usize {
+    BITS - value.leading_zeros() as usize
+}
+
+/// Pack values of given bit width into words, accumulating
+/// the bits of each word in a buffer.
+fn pack(values: &[usize], bit_width: usize) -> Vec<usize> {
+    let mut data = Vec::with_capacity(num_words(values.len(), bit_width));
+    let mut word = 0;
+    let mut filled = 0;
+    for &value in values {
+        word |= value << filled;
+        filled += bit_width;
+        if filled >= BITS {
+            data.push(word);
+            filled -= BITS;
+            // The bits of the value that did not fit in the word
+            word = if filled == 0 {
+                0
+            } else {
+                value >> (bit_width - filled)
+            };
+        }
+    }
+    if filled > 0 || data.is_empty() {
+        data.push(word);
+    }
+    data
+}
+
+/// Pack values of given bit width into words, computing each
+/// word independently in parallel.
+#[cfg(feature = "rayon")]
+fn par_pack(values: &[usize], bit_width: usize) -> Vec<usize> {
+    let mut data = vec![0; num_words(values.len(), bit_width)];
+    if bit_width == 0 {
+        return data;
+    }
+    data.par_iter_mut().enumerate().for_each(|(i, word)| {
+        let start = i * BITS;
+        // The values overlapping the bits of the word
+        let first = start / bit_width;
+        let last = values.len().min((start + BITS + bit_width - 1) / bit_width);
+        for (j, &value) in values.iter().enumerate().take(last).skip(first) {
+            let pos = j * bit_width;
+            if pos >= start {
+                *word |= value << (pos - start);
+            } else {
+                *word |= value >> (start - pos);
+            }
+        }
+    });
+    data
+}
+
+impl CompactArray<Vec<usize>> {
+    /// Create a compact array containing the given values, using
+    /// the minimum bit width that can represent all of them.
+    ///
+    /// The values are scanned twice: once to compute the bit width,
+    /// and once to pack them.
+    pub fn from_slice(values: &[usize]) -> Self {
+        let bit_width = min_bit_width(values.iter().fold(0, |or, &value| or | value));
+        Self {
+            data: pack(values, bit_width),
+            bit_width,
+            mask: mask(bit_width),
+            len: values.len(),
+        }
+    }
+}
+
+/// Collect values into a compact array using the minimum bit width that
+/// can represent all of them.
+///
+/// Since the bit width is known only at the end of the iteration, values
+/// are buffered before being packed. Use [`CompactArray::from_slice`]
+/// if the values are already in a slice.
+impl FromIterator<usize> for CompactArray<Vec<usize>> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        Self::from_slice(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// Collect values into an atomic compact array using the minimum bit
+/// width that can represent all of them.
+impl FromIterator<usize> for CompactArray<Vec<AtomicUsize>> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        CompactArray::<Vec<usize>>::from_iter(iter).into()
+    }
+}
+
+/// Collect values in parallel into a compact array using the minimum bit
+/// width that can represent all of them.
+///
+/// Values are buffered, and then both the bit width and the
+/// words of the array are computed in parallel.
+#[cfg(feature = "rayon")]
+impl FromParallelIterator<usize> for CompactArray<Vec<usize>> {
+    fn from_par_iter<I: IntoParallelIterator<Item = usize>>(par_iter: I) -> Self {
+        let values = par_iter.into_par_iter().collect::<Vec<_>>();
+        let bit_width = min_bit_width(values.par_iter().copied().reduce(|| 0, |a, b| a | b));
+        Self {
+            data: par_pack(&values, bit_width),
+            bit_width,
+            mask: mask(bit_width),
+            len: values.len(),
+        }
+    }
+}
+
+/// Collect values in parallel into an atomic compact array using the
+/// minimum bit width that can represent all of them.
+#[cfg(feature = "rayon")]
+impl FromParallelIterator<usize> for CompactArray<Vec<AtomicUsize>> {
+    fn from_par_iter<I: IntoParallelIterator<Item = usize>>(par_iter: I) -> Self {
+        CompactArray::<Vec<usize>>::from_par_iter(par_iter).into()
+    }
+}
+
 impl CompactArray<Vec<AtomicUsize>> {
     pub fn new_atomic(bit_width: usize, len: usize) -> Self {
         // we need at least two words to avoid branches in the gets
@@ -111,6 +413,11 @@ impl<T> VSliceCore for CompactArray<T> {
 }
 
 impl<T: AsRef<[usize]>> VSlice for CompactArray<T> {
+    type Iter<'a>
+        = CompactArrayIter<'a>
+    where
+        T: 'a;
+
     #[inline]
     unsafe fn get_unchecked(&self, index: usize) -> usize {
         let pos = index * self.bit_width;
@@ -125,6 +432,116 @@ impl<T: AsRef<[usize]>> VSlice for CompactArray<T> {
                 & self.mask
         }
     }
+
+    #[inline]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        CompactArrayIter::new(self, from)
+    }
+}
+
+impl<T: AsRef<[usize]>> CompactArray<T> {
+    /// Return an iterator over the values of the array.
+    ///
+    /// This is equivalent to [`iter_values`](VSlice::iter_values), but it
+    /// does not require [`VSlice`] to be in scope.
+    #[inline(always)]
+    pub fn iter(&self) -> CompactArrayIter<'_> {
+        CompactArrayIter::new(self, 0)
+    }
+}
+
+/// An iterator over the values of a [`CompactArray`].
+///
+/// Values are decoded from a buffer containing the bits of the current word
+/// that have not been returned yet, so each word is read once, and no position
+/// is computed after the first value.
+#[derive(Debug, Clone)]
+pub struct CompactArrayIter<'a> {
+    /// The underlying storage.
+    data: &'a [usize],
+    /// The bit width of the values stored in the array.
+    bit_width: usize,
+    /// A mask with its lowest `bit_width` bits set to one.
+    mask: usize,
+    /// The index of the next word to be loaded into the buffer.
+    word_index: usize,
+    /// The bits not yet returned, in the lowest positions.
+    buffer: usize,
+    /// The number of valid bits in the buffer.
+    fill: usize,
+    /// The number of values not yet returned.
+    remaining: usize,
+}
+
+impl<'a> CompactArrayIter<'a> {
+    /// Create an iterator over the values of `array` starting at `from`.
+    ///
+    /// # Panics
+    /// If `from` is greater than the length of `array`.
+    pub fn new<T: AsRef<[usize]>>(array: &'a CompactArray<T>, from: usize) -> Self {
+        assert!(
+            from <= array.len,
+            "Index out of bounds: {} > {}",
+            from,
+            array.len
+        );
+        let data = array.data.as_ref();
+        let pos = from * array.bit_width;
+        let (mut word_index, bit_index) = (pos / BITS, pos % BITS);
+        let (buffer, fill) = if bit_index == 0 {
+            (0, 0)
+        } else {
+            word_index += 1;
+            (data[word_index - 1] >> bit_index, BITS - bit_index)
+        };
+        Self {
+            data,
+            bit_width: array.bit_width,
+            mask: array.mask,
+            word_index,
+            buffer,
+            fill,
+            remaining: array.len - from,
+        }
+    }
+}
+
+impl<'a> Iterator for CompactArrayIter<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if self.bit_width <= self.fill {
+            let value = self.buffer & self.mask;
+            self.buffer >>= self.bit_width;
+            self.fill -= self.bit_width;
+            return Some(value);
+        }
+        // The value continues in the next word, which must exist
+        let word = unsafe { *self.data.get_unchecked(self.word_index) };
+        self.word_index += 1;
+        let value = (self.buffer | word << self.fill) & self.mask;
+        let used = self.bit_width - self.fill;
+        self.buffer = if used == BITS { 0 } else { word >> used };
+        self.fill = BITS - used;
+        Some(value)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for CompactArrayIter<'a> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.remaining
+    }
 }
 
 impl VSliceMut for CompactArray<Vec<usize>> {
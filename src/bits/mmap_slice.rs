@@ -0,0 +1,82 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Zero-copy slices of words in memory-mapped files.
+
+use anyhow::{bail, Result};
+use mmap_rs::{Mmap, MmapOptions};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A zero-copy slice of little-endian words in a memory-mapped file.
+///
+/// Slices are cheap to clone, as they share the same mapping, which is
+/// released when the last slice is dropped. Memory mapping is supported
+/// only on little-endian architectures.
+#[derive(Debug, Clone)]
+pub struct MmapSlice {
+    mmap: Arc<Mmap>,
+    offset: usize,
+    len: usize,
+}
+
+impl MmapSlice {
+    /// Map in memory the given file, whose length must be a multiple of 8.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if cfg!(target_endian = "big") {
+            bail!("Memory mapping is supported only on little-endian architectures");
+        }
+        let file = File::open(path.as_ref())?;
+        let bytes = file.metadata()?.len() as usize;
+        if bytes == 0 || bytes % 8 != 0 {
+            bail!(
+                "The file length ({}) is not a positive multiple of 8",
+                bytes
+            );
+        }
+        let mmap = unsafe { MmapOptions::new(bytes)?.with_file(&file, 0) }.map()?;
+        Ok(Self {
+            mmap: Arc::new(mmap),
+            offset: 0,
+            len: bytes / 8,
+        })
+    }
+
+    /// Return the array starting at word `pos`, which is preceded by its
+    /// length, and the position of the first word after the array.
+    pub fn array(&self, pos: usize) -> Result<(Self, usize)> {
+        let Some(&len) = self.as_ref().get(pos) else {
+            bail!("Truncated file: missing array length at word {}", pos);
+        };
+        let len = len as usize;
+        if len > self.len - pos - 1 {
+            bail!("Truncated file: array at word {} has length {}", pos, len);
+        }
+        Ok((
+            Self {
+                mmap: self.mmap.clone(),
+                offset: self.offset + pos + 1,
+                len,
+            },
+            pos + 1 + len,
+        ))
+    }
+}
+
+impl AsRef<[u64]> for MmapSlice {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u64] {
+        // Mappings are page aligned, and the length of the file has been checked
+        unsafe {
+            core::slice::from_raw_parts(
+                (self.mmap.as_ptr() as *const u64).add(self.offset),
+                self.len,
+            )
+        }
+    }
+}
@@ -6,15 +6,36 @@
 
 /*!
 
-Classes for [bit vectors](`bit_vec::BitVec`) and
-[arrays of values of bounded bit width](`compact_array::CompactArray`).
+Classes for [bit vectors](`bit_vec::BitVec`),
+[compressed bit vectors](`rrr::RRRBitVec`),
+[sparse bit vectors](`sparse_bit_vec::SparseBitVec`),
+[hybrid bitmaps](`roaring::RoaringBitVec`),
+[balanced parentheses](`bp::BalancedParens`),
+[arrays of values of bounded bit width](`compact_array::CompactArray`),
+[wavelet matrices](`wavelet_matrix::WaveletMatrix`),
+[streams of bits](`bit_stream`), and
+[slices of memory-mapped files](`mmap_slice::MmapSlice`).
 
 */
 
+pub mod bit_stream;
 pub mod bit_vec;
+pub mod bp;
 pub mod compact_array;
+pub mod mmap_slice;
+pub mod roaring;
+pub mod rrr;
+pub mod sparse_bit_vec;
+pub mod wavelet_matrix;
 
 pub mod prelude {
+    pub use super::bit_stream::*;
     pub use super::bit_vec::*;
+    pub use super::bp::*;
     pub use super::compact_array::*;
+    pub use super::mmap_slice::*;
+    pub use super::roaring::*;
+    pub use super::rrr::*;
+    pub use super::sparse_bit_vec::*;
+    pub use super::wavelet_matrix::*;
 }
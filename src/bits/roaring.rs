@@ -0,0 +1,685 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Hybrid bitmaps in the style of [Roaring bitmaps](https://roaringbitmap.org/).
+
+*/
+
+use crate::bits::bit_vec::BitVec;
+use crate::traits::prelude::*;
+use anyhow::{bail, Result};
+use common_traits::SelectInWord;
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+const CHUNK_LOG2: usize = 16;
+/// The number of bits in a chunk.
+const CHUNK_SIZE: usize = 1 << CHUNK_LOG2;
+/// The number of words of a dense chunk.
+const CHUNK_WORDS: usize = CHUNK_SIZE / BITS;
+/// The number of `u16` occupied by a dense chunk.
+const BITMAP_COST: usize = CHUNK_SIZE / 16;
+
+const ARRAY: u8 = 0;
+const BITMAP: u8 = 1;
+const RUNS: u8 = 2;
+
+/// A hybrid bitmap divided in chunks of 2¹⁶ bits, each represented
+/// in the most compact of three ways.
+///
+/// Chunks containing no ones are not represented at all. The other chunks
+/// are stored, depending on their content, as
+/// - a sorted array of the positions of their ones;
+/// - a dense bit vector of 2¹⁶ bits;
+/// - a sequence of runs of ones, each given by its start and its length.
+///
+/// Containers are laid out in flat arrays, so the structure can be
+/// serialized and memory-mapped with ε-serde. A binary search on the
+/// indices of nonempty chunks, or on the number of ones preceding each
+/// chunk, locates the chunk involved in [ranking](Rank) and
+/// [selection](Select); the query is then answered within the container.
+///
+/// Set operations decode each pair of containers involved into
+/// [bit vectors](BitVec), combine them using bulk operations, and choose again
+/// the best representation for the result.
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// let a = RoaringBitVec::from_ones(1 << 20, &[1, 70000, 70001, 70002]).unwrap();
+/// let b = RoaringBitVec::from_ones(1 << 20, &[1, 2, 70001]).unwrap();
+///
+/// assert_eq!(a.rank(70001), 2);
+/// assert_eq!(a.select(3), Some(70002));
+/// assert_eq!(a.and(&b).iter_ones().collect::<Vec<_>>(), vec![1, 70001]);
+/// ```
+///
+/// # Reference:
+/// - [Daniel Lemire, Owen Kaser, Nathan Kurz, Luca Deri, Chris O'Hara, François Saint-Jacques, and Gregory Ssi-Yan-Kai, Roaring bitmaps: Implementation of an optimized software library](https://doi.org/10.1002/spe.2560)
+#[derive(Epserde, Debug, Clone)]
+pub struct RoaringBitVec<U = Vec<usize>, T = Vec<u8>, P = Vec<u16>> {
+    len: usize,
+    number_of_ones: usize,
+    /// The index of the chunk of each container, in increasing order.
+    keys: U,
+    /// The kind of each container.
+    kinds: T,
+    /// The position of the data of each container in `positions`, for arrays
+    /// and runs, or in `words`, for bitmaps.
+    offsets: U,
+    /// The number of ones preceding each container.
+    ranks: U,
+    /// The positions of arrays, and the runs of run containers, stored
+    /// as their number followed by pairs (start, length − 1).
+    positions: P,
+    /// The words of bitmaps.
+    words: U,
+}
+
+/// A view on a container.
+enum Container<'a> {
+    Array(&'a [u16]),
+    Bitmap(&'a [usize]),
+    /// Pairs (start, length − 1).
+    Runs(&'a [u16]),
+}
+
+impl<'a> Container<'a> {
+    /// Return the number of ones before position `pos` of the chunk.
+    fn rank(&self, pos: usize) -> usize {
+        match *self {
+            Container::Array(array) => array.partition_point(|&x| (x as usize) < pos),
+            Container::Bitmap(words) => {
+                let word_index = pos / BITS;
+                let mut rank = words[..word_index]
+                    .iter()
+                    .map(|w| w.count_ones() as usize)
+                    .sum::<usize>();
+                if pos % BITS != 0 {
+                    rank += (words[word_index] & ((1 << (pos % BITS)) - 1)).count_ones() as usize;
+                }
+                rank
+            }
+            Container::Runs(runs) => runs
+                .chunks_exact(2)
+                .take_while(|run| (run[0] as usize) < pos)
+                .map(|run| (run[1] as usize + 1).min(pos - run[0] as usize))
+                .sum(),
+        }
+    }
+
+    /// Return the position in the chunk of the one of given rank.
+    fn select(&self, mut rank: usize) -> usize {
+        match *self {
+            Container::Array(array) => array[rank] as usize,
+            Container::Bitmap(words) => {
+                for (i, &word) in words.iter().enumerate() {
+                    let ones = word.count_ones() as usize;
+                    if rank < ones {
+                        return i * BITS + word.select_in_word(rank);
+                    }
+                    rank -= ones;
+                }
+                unreachable!()
+            }
+            Container::Runs(runs) => {
+                for run in runs.chunks_exact(2) {
+                    let len = run[1] as usize + 1;
+                    if rank < len {
+                        return run[0] as usize + rank;
+                    }
+                    rank -= len;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    /// Return the position in the chunk of the zero of given rank.
+    fn select_zero(&self, rank: usize) -> usize {
+        match *self {
+            Container::Array(array) => {
+                // The number of zeros before the i-th one is array[i] - i,
+                // which is nondecreasing.
+                let mut low = 0;
+                let mut high = array.len();
+                while low < high {
+                    let mid = (low + high) / 2;
+                    if array[mid] as usize - mid <= rank {
+                        low = mid + 1;
+                    } else {
+                        high = mid;
+                    }
+                }
+                rank + low
+            }
+            Container::Bitmap(words) => {
+                let mut rank = rank;
+                for (i, &word) in words.iter().enumerate() {
+                    let zeros = word.count_zeros() as usize;
+                    if rank < zeros {
+                        return i * BITS + (!word).select_in_word(rank);
+                    }
+                    rank -= zeros;
+                }
+                unreachable!()
+            }
+            Container::Runs(runs) => {
+                let mut ones = 0;
+                for run in runs.chunks_exact(2) {
+                    if run[0] as usize - ones > rank {
+                        break;
+                    }
+                    ones += run[1] as usize + 1;
+                }
+                rank + ones
+            }
+        }
+    }
+
+    /// Write the bits of the container to the given zeroed words.
+    fn decode(&self, dst: &mut [usize]) {
+        match *self {
+            Container::Array(array) => {
+                for &x in array {
+                    dst[x as usize / BITS] |= 1 << (x as usize % BITS);
+                }
+            }
+            Container::Bitmap(words) => dst.copy_from_slice(words),
+            Container::Runs(runs) => {
+                for run in runs.chunks_exact(2) {
+                    for x in run[0] as usize..=run[0] as usize + run[1] as usize {
+                        dst[x / BITS] |= 1 << (x % BITS);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The flat arrays of a [`RoaringBitVec`] under construction.
+#[derive(Default)]
+struct Builder {
+    number_of_ones: usize,
+    keys: Vec<usize>,
+    kinds: Vec<u8>,
+    offsets: Vec<usize>,
+    ranks: Vec<usize>,
+    positions: Vec<u16>,
+    words: Vec<usize>,
+}
+
+impl Builder {
+    /// Append the chunk of given index, choosing the smallest representation.
+    ///
+    /// `chunk` must contain the bits of the chunk, followed by zeros.
+    fn push(&mut self, key: usize, chunk: &[usize]) {
+        debug_assert_eq!(chunk.len(), CHUNK_WORDS);
+        let count = chunk.iter().map(|w| w.count_ones() as usize).sum::<usize>();
+        if count == 0 {
+            return;
+        }
+        // A run starts at each one preceded by a zero
+        let mut num_runs = 0;
+        let mut carry = 0;
+        for &word in chunk {
+            num_runs += (word & !((word << 1) | carry)).count_ones() as usize;
+            carry = word >> (BITS - 1);
+        }
+
+        self.keys.push(key);
+        self.ranks.push(self.number_of_ones);
+        self.number_of_ones += count;
+        // SAFETY: chunk contains exactly CHUNK_SIZE bits
+        let bits = unsafe { BitVec::from_raw_parts(chunk, CHUNK_SIZE) };
+        if count <= BITMAP_COST && count <= 2 * num_runs + 1 {
+            self.kinds.push(ARRAY);
+            self.offsets.push(self.positions.len());
+            self.positions.extend(bits.iter_ones().map(|x| x as u16));
+        } else if 2 * num_runs + 1 < BITMAP_COST {
+            self.kinds.push(RUNS);
+            self.offsets.push(self.positions.len());
+            self.positions.push(num_runs as u16);
+            for run in bits.runs() {
+                self.positions.push(run.start as u16);
+                self.positions.push((run.len() - 1) as u16);
+            }
+        } else {
+            self.kinds.push(BITMAP);
+            self.offsets.push(self.words.len());
+            self.words.extend_from_slice(chunk);
+        }
+    }
+
+    fn build(self, len: usize) -> RoaringBitVec {
+        RoaringBitVec {
+            len,
+            number_of_ones: self.number_of_ones,
+            keys: self.keys,
+            kinds: self.kinds,
+            offsets: self.offsets,
+            ranks: self.ranks,
+            positions: self.positions,
+            words: self.words,
+        }
+    }
+}
+
+impl<U, T, P> RoaringBitVec<U, T, P> {
+    /// # Safety
+    /// The arrays must satisfy the invariants of the structure; no
+    /// check is performed.
+    #[allow(clippy::too_many_arguments)]
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(
+        len: usize,
+        number_of_ones: usize,
+        keys: U,
+        kinds: T,
+        offsets: U,
+        ranks: U,
+        positions: P,
+        words: U,
+    ) -> Self {
+        Self {
+            len,
+            number_of_ones,
+            keys,
+            kinds,
+            offsets,
+            ranks,
+            positions,
+            words,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> (usize, usize, U, T, U, U, P, U) {
+        (
+            self.len,
+            self.number_of_ones,
+            self.keys,
+            self.kinds,
+            self.offsets,
+            self.ranks,
+            self.positions,
+            self.words,
+        )
+    }
+}
+
+impl RoaringBitVec {
+    /// Create a bitmap of given length with ones at the given positions.
+    ///
+    /// Return an error if the positions are not strictly increasing or
+    /// if some position is not smaller than `len`.
+    pub fn from_ones(len: usize, ones: &[usize]) -> Result<Self> {
+        if let Some(i) = ones.windows(2).position(|w| w[0] >= w[1]) {
+            bail!("Positions {} and {} are not strictly increasing", i, i + 1);
+        }
+        if let Some(&last) = ones.last() {
+            if last >= len {
+                bail!("Position too large: {} >= {}", last, len);
+            }
+        }
+        let mut builder = Builder::default();
+        let mut chunk = vec![0; CHUNK_WORDS];
+        let mut ones = ones;
+        while let Some(&first) = ones.first() {
+            let key = first >> CHUNK_LOG2;
+            let end = ones.partition_point(|&x| x >> CHUNK_LOG2 == key);
+            chunk.fill(0);
+            for &x in &ones[..end] {
+                let x = x & (CHUNK_SIZE - 1);
+                chunk[x / BITS] |= 1 << (x % BITS);
+            }
+            builder.push(key, &chunk);
+            ones = &ones[end..];
+        }
+        Ok(builder.build(len))
+    }
+}
+
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> RoaringBitVec<U, T, P> {
+    /// Return the number of nonempty chunks.
+    #[inline(always)]
+    fn num_containers(&self) -> usize {
+        self.keys.as_ref().len()
+    }
+
+    /// Return the number of ones in the container of given index.
+    #[inline(always)]
+    fn container_count(&self, i: usize) -> usize {
+        let ranks = self.ranks.as_ref();
+        ranks.get(i + 1).copied().unwrap_or(self.number_of_ones) - ranks[i]
+    }
+
+    /// Return the number of zeros preceding the container of given index.
+    #[inline(always)]
+    fn zeros_before(&self, i: usize) -> usize {
+        (self.keys.as_ref()[i] << CHUNK_LOG2) - self.ranks.as_ref()[i]
+    }
+
+    /// Return a view on the container of given index.
+    fn container(&self, i: usize) -> Container<'_> {
+        let offset = self.offsets.as_ref()[i];
+        let positions = self.positions.as_ref();
+        match self.kinds.as_ref()[i] {
+            ARRAY => Container::Array(&positions[offset..offset + self.container_count(i)]),
+            BITMAP => Container::Bitmap(&self.words.as_ref()[offset..offset + CHUNK_WORDS]),
+            _ => {
+                let num_runs = positions[offset] as usize;
+                Container::Runs(&positions[offset + 1..offset + 1 + 2 * num_runs])
+            }
+        }
+    }
+
+    /// Return the number of containers of each kind, that is, arrays,
+    /// bitmaps and runs.
+    pub fn container_stats(&self) -> (usize, usize, usize) {
+        let kinds = self.kinds.as_ref();
+        let count = |kind| kinds.iter().filter(|&&k| k == kind).count();
+        (count(ARRAY), count(BITMAP), count(RUNS))
+    }
+
+    /// Return an iterator over the positions of the ones.
+    pub fn iter_ones(&self) -> RoaringOnesIterator<'_, U, T, P> {
+        RoaringOnesIterator {
+            roaring: self,
+            next_container: 0,
+            base: 0,
+            current: None,
+        }
+    }
+
+    /// Apply `op` to the dense decoding of the chunks of `self` and `other`
+    /// for which `keep` returns true, given whether they are nonempty.
+    fn merge<V: AsRef<[usize]>, S: AsRef<[u8]>, Q: AsRef<[u16]>>(
+        &self,
+        other: &RoaringBitVec<V, S, Q>,
+        keep: impl Fn(bool, bool) -> bool,
+        op: impl Fn(&mut BitVec, &BitVec<&[usize]>),
+    ) -> RoaringBitVec {
+        assert_eq!(
+            self.len, other.len,
+            "The two bitmaps have different lengths"
+        );
+        let mut builder = Builder::default();
+        let mut a = vec![0; CHUNK_WORDS];
+        let mut b = vec![0; CHUNK_WORDS];
+        let (keys_a, keys_b) = (self.keys.as_ref(), other.keys.as_ref());
+        let (mut i, mut j) = (0, 0);
+        while i < keys_a.len() || j < keys_b.len() {
+            let key_a = keys_a.get(i).copied().unwrap_or(usize::MAX);
+            let key_b = keys_b.get(j).copied().unwrap_or(usize::MAX);
+            let key = key_a.min(key_b);
+            let (in_a, in_b) = (key_a == key, key_b == key);
+            if keep(in_a, in_b) {
+                a.fill(0);
+                b.fill(0);
+                if in_a {
+                    self.container(i).decode(&mut a);
+                }
+                if in_b {
+                    other.container(j).decode(&mut b);
+                }
+                // SAFETY: the vectors contain exactly CHUNK_SIZE bits
+                let mut bits_a = unsafe { BitVec::from_raw_parts(a, CHUNK_SIZE) };
+                op(&mut bits_a, &unsafe {
+                    BitVec::from_raw_parts(&b[..], CHUNK_SIZE)
+                });
+                a = bits_a.into_raw_parts().0;
+                builder.push(key, &a);
+            }
+            i += in_a as usize;
+            j += in_b as usize;
+        }
+        builder.build(self.len)
+    }
+
+    /// Return the intersection of this bitmap and `other`.
+    ///
+    /// # Panics
+    /// If the two bitmaps have different lengths.
+    pub fn and<V: AsRef<[usize]>, S: AsRef<[u8]>, Q: AsRef<[u16]>>(
+        &self,
+        other: &RoaringBitVec<V, S, Q>,
+    ) -> RoaringBitVec {
+        self.merge(other, |a, b| a && b, |a, b| a.and_in_place(b))
+    }
+
+    /// Return the union of this bitmap and `other`.
+    ///
+    /// # Panics
+    /// If the two bitmaps have different lengths.
+    pub fn or<V: AsRef<[usize]>, S: AsRef<[u8]>, Q: AsRef<[u16]>>(
+        &self,
+        other: &RoaringBitVec<V, S, Q>,
+    ) -> RoaringBitVec {
+        self.merge(other, |a, b| a || b, |a, b| a.or_in_place(b))
+    }
+
+    /// Return the symmetric difference of this bitmap and `other`.
+    ///
+    /// # Panics
+    /// If the two bitmaps have different lengths.
+    pub fn xor<V: AsRef<[usize]>, S: AsRef<[u8]>, Q: AsRef<[u16]>>(
+        &self,
+        other: &RoaringBitVec<V, S, Q>,
+    ) -> RoaringBitVec {
+        self.merge(other, |a, b| a || b, |a, b| a.xor_in_place(b))
+    }
+
+    /// Return the difference between this bitmap and `other`.
+    ///
+    /// # Panics
+    /// If the two bitmaps have different lengths.
+    pub fn and_not<V: AsRef<[usize]>, S: AsRef<[u8]>, Q: AsRef<[u16]>>(
+        &self,
+        other: &RoaringBitVec<V, S, Q>,
+    ) -> RoaringBitVec {
+        self.merge(other, |a, _| a, |a, b| a.and_not_in_place(b))
+    }
+}
+
+impl<U, T, P> BitLength for RoaringBitVec<U, T, P> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<U, T, P> BitCount for RoaringBitVec<U, T, P> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.number_of_ones
+    }
+}
+
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> Rank for RoaringBitVec<U, T, P> {
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let key = pos >> CHUNK_LOG2;
+        let keys = self.keys.as_ref();
+        let i = keys.partition_point(|&k| k < key);
+        if i == keys.len() {
+            self.number_of_ones
+        } else if keys[i] == key {
+            self.ranks.as_ref()[i] + self.container(i).rank(pos & (CHUNK_SIZE - 1))
+        } else {
+            self.ranks.as_ref()[i]
+        }
+    }
+}
+
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> RankZero for RoaringBitVec<U, T, P> {}
+
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> Select for RoaringBitVec<U, T, P> {
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        // The last container with at most rank ones before it
+        let i = self.ranks.as_ref().partition_point(|&r| r <= rank) - 1;
+        (self.keys.as_ref()[i] << CHUNK_LOG2)
+            + self.container(i).select(rank - self.ranks.as_ref()[i])
+    }
+}
+
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> SelectZero for RoaringBitVec<U, T, P> {
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        // The number of containers with at most rank zeros before them
+        let mut low = 0;
+        let mut high = self.num_containers();
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.zeros_before(mid) <= rank {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == 0 {
+            // No ones before the zero
+            return rank;
+        }
+
+        let i = low - 1;
+        let start = self.keys.as_ref()[i] << CHUNK_LOG2;
+        let residual = rank - self.zeros_before(i);
+        let zeros = CHUNK_SIZE.min(self.len - start) - self.container_count(i);
+        if residual < zeros {
+            start + self.container(i).select_zero(residual)
+        } else {
+            // The zero is in the empty chunks following the container
+            start + CHUNK_SIZE + residual - zeros
+        }
+    }
+}
+
+/// Choose the best representation for each chunk of a bit vector.
+impl<B: BitLength + AsRef<[usize]>> ConvertTo<RoaringBitVec> for B {
+    fn convert_to(self) -> Result<RoaringBitVec> {
+        let len = self.len();
+        let words = &self.as_ref()[..(len + BITS - 1) / BITS];
+        let mut builder = Builder::default();
+        let mut padded = vec![0; CHUNK_WORDS];
+        for (key, chunk) in words.chunks(CHUNK_WORDS).enumerate() {
+            if chunk.len() == CHUNK_WORDS {
+                builder.push(key, chunk);
+            } else {
+                // The last chunk might be partial
+                padded.fill(0);
+                padded[..chunk.len()].copy_from_slice(chunk);
+                builder.push(key, &padded);
+            }
+        }
+        Ok(builder.build(len))
+    }
+}
+
+/// Decode a hybrid bitmap into a bit vector.
+impl<U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> ConvertTo<BitVec>
+    for RoaringBitVec<U, T, P>
+{
+    fn convert_to(self) -> Result<BitVec> {
+        let mut words = vec![0; (self.len + CHUNK_SIZE - 1) / CHUNK_SIZE * CHUNK_WORDS];
+        for (i, &key) in self.keys.as_ref().iter().enumerate() {
+            self.container(i)
+                .decode(&mut words[key * CHUNK_WORDS..(key + 1) * CHUNK_WORDS]);
+        }
+        words.truncate((self.len + BITS - 1) / BITS);
+        // SAFETY: words contains exactly the words needed by len bits
+        Ok(unsafe { BitVec::from_raw_parts(words, self.len) })
+    }
+}
+
+/// The state of iteration within a container.
+enum ContainerIter<'a> {
+    Array(core::slice::Iter<'a, u16>),
+    Bitmap {
+        words: &'a [usize],
+        word_index: usize,
+        /// The current word, with the bits already returned cleared.
+        word: usize,
+    },
+    Runs {
+        runs: core::slice::ChunksExact<'a, u16>,
+        current: core::ops::Range<usize>,
+    },
+}
+
+impl<'a> Iterator for ContainerIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self {
+            ContainerIter::Array(iter) => iter.next().map(|&x| x as usize),
+            ContainerIter::Bitmap {
+                words,
+                word_index,
+                word,
+            } => {
+                while *word == 0 {
+                    *word_index += 1;
+                    *word = *words.get(*word_index)?;
+                }
+                let bit = word.trailing_zeros() as usize;
+                *word &= *word - 1;
+                Some(*word_index * BITS + bit)
+            }
+            ContainerIter::Runs { runs, current } => loop {
+                if let Some(x) = current.next() {
+                    return Some(x);
+                }
+                let run = runs.next()?;
+                *current = run[0] as usize..run[0] as usize + run[1] as usize + 1;
+            },
+        }
+    }
+}
+
+/// An iterator over the positions of the ones of a [`RoaringBitVec`].
+pub struct RoaringOnesIterator<'a, U, T, P> {
+    roaring: &'a RoaringBitVec<U, T, P>,
+    /// The index of the next container to iterate over.
+    next_container: usize,
+    /// The position of the first bit of the current container.
+    base: usize,
+    current: Option<ContainerIter<'a>>,
+}
+
+impl<'a, U: AsRef<[usize]>, T: AsRef<[u8]>, P: AsRef<[u16]>> Iterator
+    for RoaringOnesIterator<'a, U, T, P>
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(x) = self.current.as_mut().and_then(|iter| iter.next()) {
+                return Some(self.base + x);
+            }
+            if self.next_container == self.roaring.num_containers() {
+                return None;
+            }
+            let i = self.next_container;
+            self.next_container += 1;
+            self.base = self.roaring.keys.as_ref()[i] << CHUNK_LOG2;
+            self.current = Some(match self.roaring.container(i) {
+                Container::Array(array) => ContainerIter::Array(array.iter()),
+                Container::Bitmap(words) => ContainerIter::Bitmap {
+                    words,
+                    word_index: 0,
+                    word: words[0],
+                },
+                Container::Runs(runs) => ContainerIter::Runs {
+                    runs: runs.chunks_exact(2),
+                    current: 0..0,
+                },
+            });
+        }
+    }
+}
@@ -0,0 +1,341 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Compressed bit vectors using the Raman–Raman–Rao class/offset encoding.
+
+*/
+
+use crate::bits::bit_stream::{BitReader, BitWriter};
+use crate::traits::prelude::*;
+use anyhow::Result;
+use common_traits::SelectInWord;
+use epserde::*;
+
+/// The number of bits in a block.
+const BLOCK_SIZE: usize = 63;
+/// The number of blocks between two samples.
+const BLOCKS_PER_SAMPLE: usize = 32;
+
+/// Return the binomial coefficients C(n, k) for 0 ≤ n, k < 64.
+const fn binomials() -> [[u64; 64]; 64] {
+    let mut binomials = [[0; 64]; 64];
+    let mut n = 0;
+    while n < 64 {
+        binomials[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            binomials[n][k] = binomials[n - 1][k - 1] + binomials[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    binomials
+}
+
+static BINOMIALS: [[u64; 64]; 64] = binomials();
+
+/// The number of bits of the offset of a block of given class.
+const OFFSET_WIDTHS: [u8; BLOCK_SIZE + 1] = {
+    let binomials = binomials();
+    let mut widths = [0; BLOCK_SIZE + 1];
+    let mut class = 0;
+    while class <= BLOCK_SIZE {
+        let n = binomials[BLOCK_SIZE][class];
+        widths[class] = if n == 1 {
+            0
+        } else {
+            (64 - (n - 1).leading_zeros()) as u8
+        };
+        class += 1;
+    }
+    widths
+};
+
+/// Return the offset of a block, that is, its rank in lexicographical
+/// order among the blocks with the same number of ones.
+#[inline(always)]
+fn encode(block: u64) -> u64 {
+    let mut ones = block.count_ones() as usize;
+    let mut offset = 0;
+    let mut i = BLOCK_SIZE;
+    while ones > 0 {
+        i -= 1;
+        if block & (1 << i) != 0 {
+            offset += BINOMIALS[i][ones];
+            ones -= 1;
+        }
+    }
+    offset
+}
+
+/// Return the block with given class and offset.
+#[inline(always)]
+fn decode(class: usize, mut offset: u64) -> u64 {
+    let mut ones = class;
+    let mut block = 0;
+    let mut i = BLOCK_SIZE;
+    while ones > 0 {
+        i -= 1;
+        if offset >= BINOMIALS[i][ones] {
+            block |= 1 << i;
+            offset -= BINOMIALS[i][ones];
+            ones -= 1;
+        }
+    }
+    block
+}
+
+/// A compressed bit vector using the Raman–Raman–Rao representation.
+///
+/// The bit vector is divided in blocks of 63 bits. For each block we store its
+/// class, that is, its number of ones, and its offset, that is, its index
+/// among the blocks with the same class, using ⌈log₂ C(63, class)⌉ bits.
+/// Blocks that are very sparse or very dense thus use few bits. Every 32
+/// blocks we sample the number of ones and the position of the next offset,
+/// so ranking requires scanning at most 31 classes and decoding a single
+/// block. Selection performs a binary search on the samples.
+///
+/// The structure is built from any bit vector using [`ConvertTo`].
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// let mut bits = BitVec::new(1000);
+/// for i in (0..1000).step_by(100) {
+///     bits.set(i, true);
+/// }
+/// let rrr: RRRBitVec = bits.convert_to().unwrap();
+///
+/// assert_eq!(rrr.rank(150), 2);
+/// assert_eq!(rrr.select(3), Some(300));
+/// assert_eq!(rrr.select_zero(0), Some(1));
+/// ```
+///
+/// # Reference:
+/// - [Rajeev Raman, Venkatesh Raman, and Srinivasa Rao Satti, Succinct indexable dictionaries with applications to encoding k-ary trees, prefix sums and multisets](https://doi.org/10.1145/1290672.1290680)
+#[derive(Epserde, Debug, Clone)]
+pub struct RRRBitVec<C = Vec<u8>, D = Vec<usize>> {
+    len: usize,
+    number_of_ones: usize,
+    /// The class of each block.
+    classes: C,
+    /// The offsets of the blocks, concatenated.
+    offsets: D,
+    /// For every sample, the number of ones before the sampled block
+    /// and the position of its offset.
+    samples: D,
+}
+
+impl<C: AsRef<[u8]>, D: AsRef<[usize]>> RRRBitVec<C, D> {
+    /// Return the number of ones before the block sampled by `sample`,
+    /// and the position of the offset of the block.
+    #[inline(always)]
+    fn sample(&self, sample: usize) -> (usize, usize) {
+        let samples = self.samples.as_ref();
+        (samples[sample * 2], samples[sample * 2 + 1])
+    }
+
+    /// Return the bits of a block given the position of its offset.
+    #[inline(always)]
+    fn block(&self, block: usize, offset_pos: usize) -> u64 {
+        let class = self.classes.as_ref()[block] as usize;
+        let mut reader = BitReader::new(self.offsets.as_ref());
+        reader.set_position(offset_pos);
+        decode(class, reader.read_bits(OFFSET_WIDTHS[class] as usize))
+    }
+
+    /// Scan the blocks after the sample of `block` up to `block`, excluded,
+    /// returning the number of ones before `block` and the position of
+    /// its offset.
+    #[inline(always)]
+    fn scan_to(&self, block: usize) -> (usize, usize) {
+        let sample = block / BLOCKS_PER_SAMPLE;
+        let (mut rank, mut offset_pos) = self.sample(sample);
+        for &class in &self.classes.as_ref()[sample * BLOCKS_PER_SAMPLE..block] {
+            rank += class as usize;
+            offset_pos += OFFSET_WIDTHS[class as usize] as usize;
+        }
+        (rank, offset_pos)
+    }
+
+    /// Return an iterator over the positions of the ones.
+    pub fn iter_ones(&self) -> RRROnesIterator<'_, C, D> {
+        RRROnesIterator {
+            rrr: self,
+            block: 0,
+            offset_pos: 0,
+            word: 0,
+        }
+    }
+}
+
+impl<C, D> BitLength for RRRBitVec<C, D> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<C, D> BitCount for RRRBitVec<C, D> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.number_of_ones
+    }
+}
+
+impl<C: AsRef<[u8]>, D: AsRef<[usize]>> Rank for RRRBitVec<C, D> {
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        if pos == self.len {
+            return self.number_of_ones;
+        }
+        let block = pos / BLOCK_SIZE;
+        let (rank, offset_pos) = self.scan_to(block);
+        let bits = self.block(block, offset_pos);
+        rank + (bits & ((1 << (pos % BLOCK_SIZE)) - 1)).count_ones() as usize
+    }
+}
+
+impl<C: AsRef<[u8]>, D: AsRef<[usize]>> RankZero for RRRBitVec<C, D> {}
+
+impl<C: AsRef<[u8]>, D: AsRef<[usize]>> Select for RRRBitVec<C, D> {
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        let num_samples = self.samples.as_ref().len() / 2;
+        // The last sample with at most rank ones before it
+        let mut low = 0;
+        let mut high = num_samples;
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if self.sample(mid).0 <= rank {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let (mut residual, mut offset_pos) = self.sample(low);
+        residual = rank - residual;
+        let classes = self.classes.as_ref();
+        let mut block = low * BLOCKS_PER_SAMPLE;
+        loop {
+            let class = classes[block] as usize;
+            if residual < class {
+                break;
+            }
+            residual -= class;
+            offset_pos += OFFSET_WIDTHS[class] as usize;
+            block += 1;
+        }
+        block * BLOCK_SIZE + self.block(block, offset_pos).select_in_word(residual)
+    }
+}
+
+impl<C: AsRef<[u8]>, D: AsRef<[usize]>> SelectZero for RRRBitVec<C, D> {
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        let num_samples = self.samples.as_ref().len() / 2;
+        let zeros = |sample: usize| sample * BLOCKS_PER_SAMPLE * BLOCK_SIZE - self.sample(sample).0;
+        // The last sample with at most rank zeros before it
+        let mut low = 0;
+        let mut high = num_samples;
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if zeros(mid) <= rank {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let mut residual = rank - zeros(low);
+        let mut offset_pos = self.sample(low).1;
+        let classes = self.classes.as_ref();
+        let mut block = low * BLOCKS_PER_SAMPLE;
+        loop {
+            let class = classes[block] as usize;
+            if residual < BLOCK_SIZE - class {
+                break;
+            }
+            residual -= BLOCK_SIZE - class;
+            offset_pos += OFFSET_WIDTHS[class] as usize;
+            block += 1;
+        }
+        // The complement sets the unused highest bit, which is never selected
+        block * BLOCK_SIZE + (!self.block(block, offset_pos)).select_in_word(residual)
+    }
+}
+
+/// Compress a bit vector.
+impl<B: BitLength + AsRef<[usize]>> ConvertTo<RRRBitVec> for B {
+    fn convert_to(self) -> Result<RRRBitVec> {
+        let len = self.len();
+        let num_blocks = (len + BLOCK_SIZE - 1) / BLOCK_SIZE;
+        let mut reader = BitReader::new(self.as_ref());
+        let mut classes = Vec::with_capacity(num_blocks);
+        let mut offsets = BitWriter::default();
+        let mut samples = Vec::with_capacity((num_blocks / BLOCKS_PER_SAMPLE + 1) * 2);
+        let mut number_of_ones = 0;
+
+        for block in 0..num_blocks {
+            if block % BLOCKS_PER_SAMPLE == 0 {
+                samples.push(number_of_ones);
+                samples.push(offsets.len());
+            }
+            let bits = reader.read_bits(BLOCK_SIZE.min(len - block * BLOCK_SIZE));
+            let class = bits.count_ones() as usize;
+            classes.push(class as u8);
+            offsets.write_bits(encode(bits), OFFSET_WIDTHS[class] as usize);
+            number_of_ones += class;
+        }
+
+        let (offsets, _) = offsets.into_bit_vec().into_raw_parts();
+        Ok(RRRBitVec {
+            len,
+            number_of_ones,
+            classes,
+            offsets,
+            samples,
+        })
+    }
+}
+
+/// An iterator over the positions of the ones of an [`RRRBitVec`],
+/// decoding a block at a time.
+pub struct RRROnesIterator<'a, C, D> {
+    rrr: &'a RRRBitVec<C, D>,
+    /// The index of the next block to decode.
+    block: usize,
+    /// The position of the offset of the next block to decode.
+    offset_pos: usize,
+    /// The current block, with the bits already returned cleared.
+    word: u64,
+}
+
+impl<'a, C: AsRef<[u8]>, D: AsRef<[usize]>> Iterator for RRROnesIterator<'a, C, D> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let classes = self.rrr.classes.as_ref();
+        while self.word == 0 {
+            // Skip empty blocks without decoding them
+            loop {
+                let &class = classes.get(self.block)?;
+                if class != 0 {
+                    break;
+                }
+                self.block += 1;
+            }
+            let class = classes[self.block] as usize;
+            self.word = self.rrr.block(self.block, self.offset_pos);
+            self.offset_pos += OFFSET_WIDTHS[class] as usize;
+            self.block += 1;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some((self.block - 1) * BLOCK_SIZE + bit)
+    }
+}
@@ -0,0 +1,179 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Sparse bit vectors storing the positions of their ones in an
+[Elias–Fano](crate::dict::elias_fano::EliasFano) representation.
+
+*/
+
+use crate::prelude::*;
+use anyhow::{bail, Result};
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+
+/// An immutable bit vector representing the positions of its ones
+/// using an [`EliasFano`] structure.
+///
+/// The space used is about 2 + log₂(`len` / `count`) bits per one, which is much
+/// smaller than `len` bits when ones are rare. Since the structure implements
+/// [`BitLength`], [`BitCount`], [`Rank`], [`RankZero`], [`Select`] and
+/// [`SelectZero`], it can replace a bit vector in code that is generic
+/// over those traits.
+///
+/// Ranking is performed by computing a successor; selection of a one
+/// by accessing the underlying sequence; selection of a zero by a
+/// binary search on the underlying sequence. As for [`EliasFano`], the high
+/// bits can be enriched with [indices](crate::rank_sel) using [`ConvertTo`].
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// let sparse = SparseBitVec::from_ones(1000, &[3, 10, 500]).unwrap();
+///
+/// assert_eq!(sparse.rank(11), 2);
+/// assert_eq!(sparse.select(2), Some(500));
+/// assert_eq!(sparse.select_zero(3), Some(4));
+///
+/// // Add an index on the ones of the high bits (accelerates select)
+/// let sparse: SparseBitVec<QuantumIndex<CountBitVec>> = sparse.convert_to().unwrap();
+/// assert_eq!(sparse.select(1), Some(10));
+/// ```
+#[derive(Epserde, Debug, Clone)]
+pub struct SparseBitVec<H = CountBitVec, L = CompactArray> {
+    ef: EliasFano<H, L>,
+}
+
+impl<H, L> SparseBitVec<H, L> {
+    /// # Safety
+    /// The values of `ef` must be strictly increasing, and its
+    /// [upper bound](EliasFano::upper_bound) is the length of the bit vector.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(ef: EliasFano<H, L>) -> Self {
+        Self { ef }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> EliasFano<H, L> {
+        self.ef
+    }
+}
+
+impl SparseBitVec {
+    /// Create a sparse bit vector of given length with ones at the given positions.
+    ///
+    /// Return an error if the positions are not strictly increasing or
+    /// if some position is not smaller than `len`.
+    pub fn from_ones(len: usize, ones: &[usize]) -> Result<Self> {
+        if let Some(i) = ones.windows(2).position(|w| w[0] >= w[1]) {
+            bail!("Positions {} and {} are not strictly increasing", i, i + 1);
+        }
+        let mut efb = EliasFanoBuilder::new(ones.len(), len);
+        for &one in ones {
+            efb.push(one)?;
+        }
+        Ok(Self { ef: efb.build() })
+    }
+}
+
+impl<H: Select + AsRef<[usize]>, L: VSlice> SparseBitVec<H, L> {
+    /// Return an iterator over the positions of the ones.
+    pub fn iter_ones(&self) -> EliasFanoIterator<'_, H, L> {
+        self.ef.iter()
+    }
+}
+
+impl<H, L> BitLength for SparseBitVec<H, L> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.ef.upper_bound()
+    }
+}
+
+impl<H, L> BitCount for SparseBitVec<H, L> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.ef.len()
+    }
+}
+
+impl<H: Select + SelectZero + AsRef<[usize]>, L: VSlice> Rank for SparseBitVec<H, L> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        // The index of the first one at or after pos
+        self.ef
+            .successor(&pos)
+            .map_or(self.ef.len(), |(index, _)| index)
+    }
+}
+
+impl<H: Select + SelectZero + AsRef<[usize]>, L: VSlice> RankZero for SparseBitVec<H, L> {}
+
+impl<H: Select + AsRef<[usize]>, L: VSlice> Select for SparseBitVec<H, L> {
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.ef.get_unchecked(rank)
+    }
+}
+
+impl<H: Select + AsRef<[usize]>, L: VSlice> SelectZero for SparseBitVec<H, L> {
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        // The number of zeros before the i-th one, that is, the i-th value
+        // minus i, is nondecreasing: we look for the number of ones
+        // preceded by at most rank zeros.
+        let mut low = 0;
+        let mut high = self.ef.len();
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.ef.get_unchecked(mid) - mid <= rank {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        rank + low
+    }
+}
+
+/// Store the positions of the ones of a bit vector.
+impl<B: BitLength + AsRef<[usize]>> ConvertTo<SparseBitVec> for B {
+    fn convert_to(self) -> Result<SparseBitVec> {
+        let len = self.len();
+        let words = self.as_ref();
+        let words = &words[..(len + BITS - 1) / BITS];
+        let count = words.iter().map(|w| w.count_ones() as usize).sum();
+
+        let mut efb = EliasFanoBuilder::new(count, len);
+        for (i, &word) in words.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                // SAFETY: positions are increasing and, since bits past
+                // the end are zero, smaller than len
+                unsafe { efb.push_unchecked(i * BITS + word.trailing_zeros() as usize) };
+                word &= word - 1;
+            }
+        }
+        Ok(SparseBitVec { ef: efb.build() })
+    }
+}
+
+/// Provide conversion between sparse bit vectors whose underlying
+/// [`EliasFano`] structures are convertible into one another.
+impl<H1, L1, H2, L2> ConvertTo<SparseBitVec<H1, L1>> for SparseBitVec<H2, L2>
+where
+    H2: ConvertTo<H1>,
+    L2: ConvertTo<L1>,
+{
+    #[inline(always)]
+    fn convert_to(self) -> Result<SparseBitVec<H1, L1>> {
+        Ok(SparseBitVec {
+            ef: self.ef.convert_to()?,
+        })
+    }
+}
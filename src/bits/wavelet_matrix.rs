@@ -0,0 +1,405 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Wavelet matrices over sequences of values of bounded bit width.
+
+*/
+
+use crate::prelude::*;
+use anyhow::Result;
+use core::cmp::Reverse;
+use epserde::*;
+use std::collections::BinaryHeap;
+
+const BITS: usize = usize::BITS as usize;
+
+/// A wavelet matrix representing a sequence of values of bounded bit width.
+///
+/// A wavelet matrix with bit width *w* over a sequence of length *n* is
+/// made of *w* levels of *n* bits. The first level contains the most
+/// significant bit of each value; then, the values are stably partitioned
+/// so that values with that bit zero precede those with that bit one,
+/// and the next level contains the next bit of each value in the new order,
+/// and so on. Besides [accessing](WaveletMatrix::access) the values, this
+/// representation makes it possible to [count](WaveletMatrix::rank) and
+/// [locate](WaveletMatrix::select) the occurrences of a value, and to
+/// answer queries over ranges of positions such as [counting the values in a
+/// range](WaveletMatrix::range_count), [order
+/// statistics](WaveletMatrix::quantile) and [most frequent
+/// values](WaveletMatrix::top_k), all in time proportional to *w*.
+///
+/// The levels are concatenated in a single bit vector supporting [ranking](Rank)
+/// and [selection](Select) of ones and [zeros](SelectZero); the
+/// default backend adds [`Rank9`], [`QuantumIndex`], and
+/// [`QuantumZeroIndex`] to a [`CountBitVec`]. Ranks at the start of
+/// each level are stored separately.
+///
+/// Since the structure implements [`VSlice`], it can replace a
+/// [`CompactArray`] in code that only reads values.
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// let mut seq = CompactArray::new(3, 8);
+/// for (i, &v) in [5, 1, 3, 5, 7, 1, 5, 0].iter().enumerate() {
+///     seq.set(i, v);
+/// }
+/// let wm = WaveletMatrix::new(&seq).unwrap();
+///
+/// assert_eq!(wm.access(4), 7);
+/// assert_eq!(wm.rank(5, 6), 2);
+/// assert_eq!(wm.select(5, 2), Some(6));
+/// // Values in [1..4) at positions [1..7)
+/// assert_eq!(wm.range_count(1..7, 1..4), 3);
+/// // The smallest value at positions [2..6)
+/// assert_eq!(wm.quantile(2..6, 0), Some(1));
+/// assert_eq!(wm.top_k(0..8, 2), vec![(5, 3), (1, 2)]);
+/// ```
+///
+/// # Reference:
+/// - [Francisco Claude, Gonzalo Navarro, and Alberto Ordóñez, The wavelet matrix: An efficient wavelet tree for large alphabets](https://doi.org/10.1016/j.is.2014.06.002)
+#[derive(Epserde, Debug, Clone)]
+pub struct WaveletMatrix<B = Rank9<QuantumZeroIndex<QuantumIndex<CountBitVec>>>, O = Vec<usize>> {
+    /// The concatenated levels.
+    bits: B,
+    /// The number of ones preceding each level, plus the overall number of ones.
+    ones: O,
+    /// The length of the sequence.
+    len: usize,
+    /// The bit width of the values, that is, the number of levels.
+    bit_width: usize,
+}
+
+impl<B, O> WaveletMatrix<B, O> {
+    /// # Safety
+    /// `bits` must contain the levels of a wavelet matrix with `bit_width`
+    /// levels over a sequence of length `len`, and `ones` must contain
+    /// the number of ones preceding each level, plus the overall number of ones.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(bits: B, ones: O, len: usize, bit_width: usize) -> Self {
+        Self {
+            bits,
+            ones,
+            len,
+            bit_width,
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> (B, O, usize, usize) {
+        (self.bits, self.ones, self.len, self.bit_width)
+    }
+}
+
+impl WaveletMatrix {
+    /// Build a wavelet matrix representing the given sequence, using its
+    /// [bit width](VSliceCore::bit_width).
+    pub fn new<S: VSlice + ?Sized>(seq: &S) -> Result<Self> {
+        let len = seq.len();
+        let bit_width = seq.bit_width();
+        let mut bits = BitVec::new(len * bit_width);
+        let mut ones = Vec::with_capacity(bit_width + 1);
+        ones.push(0);
+
+        let mut values = (0..len)
+            .map(|i| unsafe { seq.get_unchecked(i) })
+            .collect::<Vec<_>>();
+        let mut right = Vec::with_capacity(len);
+        for level in 0..bit_width {
+            let shift = bit_width - 1 - level;
+            let mut left = 0;
+            for i in 0..len {
+                // Since left <= i, we never overwrite unread values
+                let value = values[i];
+                if value >> shift & 1 != 0 {
+                    bits.set(level * len + i, true);
+                    right.push(value);
+                } else {
+                    values[left] = value;
+                    left += 1;
+                }
+            }
+            ones.push(ones[level] + right.len());
+            values.truncate(left);
+            values.append(&mut right);
+        }
+
+        let bits: CountBitVec = bits.into();
+        let bits: QuantumIndex<CountBitVec> = bits.convert_to()?;
+        let bits: QuantumZeroIndex<QuantumIndex<CountBitVec>> = bits.convert_to()?;
+        Ok(Self {
+            bits: bits.convert_to()?,
+            ones,
+            len,
+            bit_width,
+        })
+    }
+}
+
+impl<B: Rank + Select + SelectZero + AsRef<[usize]>, O: AsRef<[usize]>> WaveletMatrix<B, O> {
+    /// Return whether a value fits within the bit width.
+    #[inline(always)]
+    fn fits(&self, value: usize) -> bool {
+        self.bit_width == BITS || value >> self.bit_width == 0
+    }
+
+    #[inline(always)]
+    fn check_range(&self, range: &core::ops::Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range out of bounds: {:?} with length {}",
+            range,
+            self.len
+        );
+    }
+
+    /// Return the bit of the given level of a value.
+    #[inline(always)]
+    fn bit_of(&self, value: usize, level: usize) -> bool {
+        value >> (self.bit_width - 1 - level) & 1 != 0
+    }
+
+    /// Return the number of zeros of a level.
+    #[inline(always)]
+    fn zeros(&self, level: usize) -> usize {
+        let ones = self.ones.as_ref();
+        self.len - (ones[level + 1] - ones[level])
+    }
+
+    /// Return the number of ones of a level preceding a position.
+    #[inline(always)]
+    fn rank_level(&self, level: usize, pos: usize) -> usize {
+        unsafe { self.bits.rank_unchecked(level * self.len + pos) - self.ones.as_ref()[level] }
+    }
+
+    /// Return the position in the next level of the element at the given
+    /// position of a level, or of the end of a range, following a bit.
+    #[inline(always)]
+    fn descend(&self, level: usize, pos: usize, bit: bool) -> usize {
+        let rank = self.rank_level(level, pos);
+        if bit {
+            self.zeros(level) + rank
+        } else {
+            pos - rank
+        }
+    }
+
+    /// Return the value at the given position.
+    ///
+    /// # Panics
+    /// If `index` is not in [0..`len`).
+    pub fn access(&self, index: usize) -> usize {
+        panic_if_out_of_bounds!(index, self.len);
+        unsafe { self.access_unchecked(index) }
+    }
+
+    /// # Safety
+    /// `index` must be in [0..`len`).
+    unsafe fn access_unchecked(&self, mut index: usize) -> usize {
+        let words = self.bits.as_ref();
+        let mut value = 0;
+        for level in 0..self.bit_width {
+            let pos = level * self.len + index;
+            let bit = words.get_unchecked(pos / BITS) >> (pos % BITS) & 1 != 0;
+            value = value << 1 | bit as usize;
+            index = self.descend(level, index, bit);
+        }
+        value
+    }
+
+    /// Return the number of occurrences of a value before a position.
+    ///
+    /// # Panics
+    /// If `pos` is greater than `len`.
+    pub fn rank(&self, value: usize, pos: usize) -> usize {
+        self.check_range(&(0..pos));
+        if !self.fits(value) {
+            return 0;
+        }
+        let (mut start, mut end) = (0, pos);
+        for level in 0..self.bit_width {
+            let bit = self.bit_of(value, level);
+            start = self.descend(level, start, bit);
+            end = self.descend(level, end, bit);
+        }
+        end - start
+    }
+
+    /// Return the position of the occurrence of given rank of a value,
+    /// or `None` if the value does not occur so many times.
+    pub fn select(&self, value: usize, rank: usize) -> Option<usize> {
+        if !self.fits(value) {
+            return None;
+        }
+        // In the last level, occurrences of the value are consecutive
+        let (mut start, mut end) = (0, self.len);
+        for level in 0..self.bit_width {
+            let bit = self.bit_of(value, level);
+            start = self.descend(level, start, bit);
+            end = self.descend(level, end, bit);
+        }
+        if rank >= end - start {
+            return None;
+        }
+
+        // Follow the occurrence back to the first level
+        let ones = self.ones.as_ref();
+        let mut pos = start + rank;
+        for level in (0..self.bit_width).rev() {
+            let offset = level * self.len;
+            pos = unsafe {
+                if self.bit_of(value, level) {
+                    self.bits
+                        .select_unchecked(ones[level] + pos - self.zeros(level))
+                } else {
+                    self.bits.select_zero_unchecked(offset - ones[level] + pos)
+                }
+            } - offset;
+        }
+        Some(pos)
+    }
+
+    /// Return the number of values smaller than `bound` in a range of positions.
+    fn count_less(&self, range: &core::ops::Range<usize>, bound: usize) -> usize {
+        if !self.fits(bound) {
+            return range.len();
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        let mut count = 0;
+        for level in 0..self.bit_width {
+            let bit = self.bit_of(bound, level);
+            if bit {
+                // Values with a zero here are smaller
+                count +=
+                    (end - self.rank_level(level, end)) - (start - self.rank_level(level, start));
+            }
+            start = self.descend(level, start, bit);
+            end = self.descend(level, end, bit);
+        }
+        count
+    }
+
+    /// Return the number of values in the range `values` at positions
+    /// in the range `positions`.
+    ///
+    /// # Panics
+    /// If `positions` is not contained in [0..`len`).
+    pub fn range_count(
+        &self,
+        positions: core::ops::Range<usize>,
+        values: core::ops::Range<usize>,
+    ) -> usize {
+        self.check_range(&positions);
+        if values.start >= values.end {
+            return 0;
+        }
+        self.count_less(&positions, values.end) - self.count_less(&positions, values.start)
+    }
+
+    /// Return the value of given rank (starting from zero) in the sorted
+    /// sequence of values at positions in a range, or `None` if the range
+    /// does not contain so many values.
+    ///
+    /// In particular, `quantile(range, 0)` is the minimum and
+    /// `quantile(range, range.len() / 2)` is the median of the range.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..`len`).
+    pub fn quantile(&self, range: core::ops::Range<usize>, mut rank: usize) -> Option<usize> {
+        self.check_range(&range);
+        if rank >= range.len() {
+            return None;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        let mut value = 0;
+        for level in 0..self.bit_width {
+            let (start_ones, end_ones) =
+                (self.rank_level(level, start), self.rank_level(level, end));
+            let zeros = (end - end_ones) - (start - start_ones);
+            let bit = rank >= zeros;
+            if bit {
+                rank -= zeros;
+            }
+            value = value << 1 | bit as usize;
+            start = self.descend(level, start, bit);
+            end = self.descend(level, end, bit);
+        }
+        Some(value)
+    }
+
+    /// Return at most `k` of the most frequent values at positions in a
+    /// range, with their number of occurrences.
+    ///
+    /// Values are returned by decreasing frequency; values with the same
+    /// frequency by increasing value.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..`len`).
+    pub fn top_k(&self, range: core::ops::Range<usize>, k: usize) -> Vec<(usize, usize)> {
+        self.check_range(&range);
+        let mut result = Vec::with_capacity(k.min(range.len()));
+        // Subranges are visited by decreasing size, and then by increasing
+        // smallest value; since subranges in the queue represent disjoint
+        // intervals of values, this yields leaves in the desired order.
+        let mut queue = BinaryHeap::new();
+        if !range.is_empty() {
+            queue.push((range.len(), Reverse(0_usize), 0, range.start));
+        }
+        while result.len() < k {
+            let Some((count, Reverse(prefix), level, start)) = queue.pop() else {
+                break;
+            };
+            if level == self.bit_width {
+                result.push((prefix, count));
+                continue;
+            }
+            let end = start + count;
+            let bit = 1 << (self.bit_width - 1 - level);
+            for (child, child_prefix) in [(false, prefix), (true, prefix | bit)] {
+                let child_start = self.descend(level, start, child);
+                let child_count = self.descend(level, end, child) - child_start;
+                if child_count > 0 {
+                    queue.push((child_count, Reverse(child_prefix), level + 1, child_start));
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<B, O> VSliceCore for WaveletMatrix<B, O> {
+    #[inline(always)]
+    fn bit_width(&self) -> usize {
+        self.bit_width
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<B: Rank + Select + SelectZero + AsRef<[usize]>, O: AsRef<[usize]>> VSlice
+    for WaveletMatrix<B, O>
+{
+    type Iter<'a>
+        = VSliceIter<'a, Self>
+    where
+        Self: 'a;
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        self.access_unchecked(index)
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        VSliceIter::new(self, from)
+    }
+}
@@ -43,7 +43,7 @@ impl EliasFanoBuilder {
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
+        let l = if n > 0 && u >= n {
             (u as f64 / n as f64).log2().floor() as usize
         } else {
             0
@@ -125,7 +125,7 @@ impl EliasFanoAtomicBuilder {
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
+        let l = if n > 0 && u >= n {
             (u as f64 / n as f64).log2().floor() as usize
         } else {
             0
@@ -194,6 +194,12 @@ impl<H, L> EliasFano<H, L> {
         self.len() == 0
     }
 
+    /// Return the strict upper bound to the values provided at construction time.
+    #[inline]
+    pub fn upper_bound(&self) -> usize {
+        self.u
+    }
+
     /// Estimate the size of an instance.
     pub fn estimate_size(u: usize, n: usize) -> usize {
         2 * n + (n * (u as f64 / n as f64).log2().ceil() as usize)
@@ -237,6 +243,19 @@ let efoz: EliasFano<QuantumZeroIndex<QuantumIndex<CountBitVec>>, CompactArray> =
     efo.convert_to().unwrap();
 ```
 
+If the distribution of the values is very skewed, [`SimpleSelect`] provides
+selection with a bounded number of word reads:
+```rust
+use sux::prelude::*;
+let mut efb = EliasFanoBuilder::new(2, 1 << 30);
+efb.push(0);
+efb.push(1 << 29);
+let ef = efb.build();
+let efs: EliasFano<SimpleSelect<CountBitVec>, CompactArray> =
+    ef.convert_to().unwrap();
+assert_eq!(efs.get(1), 1 << 29);
+```
+
 The main trait implemented is [`IndexedDict`], which
 makes it possible to access values with [`IndexedDict::get`].
  */
@@ -290,6 +309,75 @@ impl<H: Select + AsRef<[usize]>, L: VSlice> IndexedDict for EliasFano<H, L> {
     }
 }
 
+impl<H: Select + SelectZero + AsRef<[usize]>, L: VSlice> EliasFano<H, L> {
+    /// Return the index of the first value in the bucket of the high bits
+    /// of `value`, and the position in the high bits where the bucket starts.
+    ///
+    /// `value` must be smaller than `u`, so that the bucket exists.
+    #[inline(always)]
+    fn bucket_start(&self, value: usize) -> (usize, usize) {
+        debug_assert!(value < self.u);
+        let high = value >> self.l;
+        if high == 0 {
+            (0, 0)
+        } else {
+            // The bucket starts right after the (high - 1)-th zero
+            let bit_pos = unsafe { self.high_bits.select_zero_unchecked(high - 1) } + 1;
+            (bit_pos - high, bit_pos)
+        }
+    }
+}
+
+/// Successors are computed by selecting the zero delimiting
+/// the bucket of the high bits of the value, and then scanning the bucket.
+impl<H: Select + SelectZero + AsRef<[usize]>, L: VSlice> Successor for EliasFano<H, L> {
+    fn successor(&self, value: &Self::InputValue) -> Option<(usize, Self::OutputValue)> {
+        let value = *value;
+        if value >= self.u {
+            return None;
+        }
+        let (index, bit_pos) = self.bucket_start(value);
+        if index >= self.n {
+            return None;
+        }
+        EliasFanoIterator::new_from_bit_pos(self, index, bit_pos)
+            .enumerate()
+            .find(|&(_, x)| x >= value)
+            .map(|(i, x)| (index + i, x))
+    }
+}
+
+/// Predecessors are computed by selecting the zero delimiting
+/// the bucket of the high bits of the value, and then scanning the bucket.
+impl<H: Select + SelectZero + AsRef<[usize]>, L: VSlice> Predecessor for EliasFano<H, L> {
+    fn predecessor(&self, value: &Self::InputValue) -> Option<(usize, Self::OutputValue)> {
+        let value = *value;
+        if value == 0 || self.n == 0 {
+            return None;
+        }
+        if value >= self.u {
+            // All values are smaller than u
+            return Some((self.n - 1, unsafe { self.get_unchecked(self.n - 1) }));
+        }
+        let (index, bit_pos) = self.bucket_start(value);
+        // All values before the bucket are smaller than value
+        let mut res = if index == 0 {
+            None
+        } else {
+            Some((index - 1, unsafe { self.get_unchecked(index - 1) }))
+        };
+        if index < self.n {
+            for (i, x) in EliasFanoIterator::new_from_bit_pos(self, index, bit_pos).enumerate() {
+                if x >= value {
+                    break;
+                }
+                res = Some((index + i, x));
+            }
+        }
+        res
+    }
+}
+
 impl<H1, L1, H2, L2> ConvertTo<EliasFano<H1, L1>> for EliasFano<H2, L2>
 where
     H2: ConvertTo<H1>,
@@ -339,6 +427,13 @@ impl<'a, H: Select + AsRef<[usize]>, L: VSlice> EliasFanoIterator<'a, H, L> {
             panic!("Index out of bounds: {} > {}", start_index, ef.len());
         }
         let bit_pos = unsafe { ef.high_bits.select_unchecked(start_index) };
+        Self::new_from_bit_pos(ef, start_index, bit_pos)
+    }
+
+    /// Create an iterator starting from `start_index`, given the
+    /// position `bit_pos` in the high bits of the one of rank `start_index`,
+    /// or of any position between the previous one and it.
+    fn new_from_bit_pos(ef: &'a EliasFano<H, L>, start_index: usize, bit_pos: usize) -> Self {
         let word_idx = bit_pos / (core::mem::size_of::<usize>() * 8);
         let bits_to_clean = bit_pos % (core::mem::size_of::<usize>() * 8);
 
@@ -13,6 +13,7 @@ pub mod mph;
 pub mod rank_sel;
 pub mod sf;
 pub mod traits;
+pub mod tree;
 
 pub mod prelude {
     pub use crate::bits::prelude::*;
@@ -21,4 +22,5 @@ pub mod prelude {
     pub use crate::rank_sel::prelude::*;
     pub use crate::sf::*;
     pub use crate::traits::prelude::*;
+    pub use crate::tree::prelude::*;
 }
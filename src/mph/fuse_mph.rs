@@ -0,0 +1,258 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Minimal perfect hash functions based on fuse graphs.
+
+use crate::prelude::*;
+use crate::sf::fuse::{
+    chunk, chunk_bits, count_sort, edge, fuse_parameters, has_duplicates, next_seed, peel, Remap,
+    MAX_ATTEMPTS, SEED,
+};
+use anyhow::{bail, Result};
+use epserde::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+const BITS: usize = usize::BITS as usize;
+/// The number of 2-bit values in a word.
+const VALUES_PER_WORD: usize = BITS / 2;
+/// The number of words of values between two counts.
+const WORDS_PER_BLOCK: usize = 8;
+
+/// Return the number of nonzero 2-bit values in a word.
+#[inline(always)]
+fn nonzero_pairs(word: usize) -> usize {
+    ((word | word >> 1) & (usize::MAX / 3)).count_ones() as usize
+}
+
+/// A minimal perfect hash function based on fuse graphs.
+///
+/// Keys are mapped to the edges of a fuse 3-hypergraph exactly as in
+/// [`Function`](crate::sf::fuse::Function), but each vertex stores just a
+/// 2-bit value. The values of the three vertices of an edge sum modulo 3
+/// to the index of the vertex of the edge that was its hinge during peeling;
+/// since hinges are distinct, and they are the only vertices with a nonzero
+/// value, the number of nonzero values preceding the hinge is a bijection
+/// between the keys and `[0..n)`.
+///
+/// Ranking uses a count of nonzero values every 256 values, so the
+/// structure uses about 2.25 bits per vertex.
+///
+/// # Examples
+/// ```
+/// use sux::mph::fuse_mph::FuseMPH;
+///
+/// let keys = (0..1000_u64).collect::<Vec<_>>();
+/// let mph = FuseMPH::new(&keys).unwrap();
+/// mph.verify(&keys).unwrap();
+///
+/// assert!(keys.iter().all(|key| mph.get(key) < keys.len()));
+/// ```
+#[derive(Epserde, Debug, Clone)]
+pub struct FuseMPH<T, A = Vec<usize>, C = Vec<usize>> {
+    seed: u64,
+    l: usize,
+    num_keys: usize,
+    chunk_mask: u64,
+    segment_size: usize,
+    values: A,
+    counts: C,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T, A, C> FuseMPH<T, A, C> {
+    /// Return the number of keys of the function.
+    pub fn len(&self) -> usize {
+        self.num_keys
+    }
+
+    /// Return whether the function has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Remap, A: AsRef<[usize]>, C: AsRef<[usize]>> FuseMPH<T, A, C> {
+    /// Return the 2-bit value of a vertex.
+    #[inline(always)]
+    fn value(&self, pos: usize) -> usize {
+        self.values.as_ref()[pos / VALUES_PER_WORD] >> (pos % VALUES_PER_WORD * 2) & 3
+    }
+
+    /// Return the number of nonzero values before a position.
+    #[inline(always)]
+    fn rank(&self, pos: usize) -> usize {
+        let values = self.values.as_ref();
+        let word = pos / VALUES_PER_WORD;
+        let block = word / WORDS_PER_BLOCK;
+        let mut rank = self.counts.as_ref()[block];
+        for &w in &values[block * WORDS_PER_BLOCK..word] {
+            rank += nonzero_pairs(w);
+        }
+        rank + nonzero_pairs(values[word] & ((1 << (pos % VALUES_PER_WORD * 2)) - 1))
+    }
+
+    /// Return the index associated with a signature.
+    pub fn get_by_sig(&self, sig: &[u64; 2]) -> usize {
+        let chunk_offset = chunk(sig, self.chunk_mask) * self.segment_size * (self.l + 2);
+        let edge = edge(sig, self.l, self.segment_size).map(|v| v + chunk_offset);
+        let hinge = (self.value(edge[0]) + self.value(edge[1]) + self.value(edge[2])) % 3;
+        self.rank(edge[hinge])
+    }
+
+    /// Return the index in `[0..n)` associated with a key.
+    ///
+    /// If the key was not used to build the function, the result is
+    /// arbitrary.
+    #[inline(always)]
+    pub fn get(&self, key: &T) -> usize {
+        self.get_by_sig(&T::remap(key, self.seed))
+    }
+
+    /// Check that the function is a bijection between `keys` and
+    /// `[0..n)`, returning an error describing the first problem found.
+    pub fn verify(&self, keys: &[T]) -> Result<()> {
+        if keys.len() != self.len() {
+            bail!(
+                "The number of keys ({}) differs from the size of the function ({})",
+                keys.len(),
+                self.len()
+            );
+        }
+        let mut seen = BitVec::new(keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            let index = self.get(key);
+            if index >= keys.len() {
+                bail!(
+                    "Key {} is mapped to {}, which is not smaller than {}",
+                    i,
+                    index,
+                    keys.len()
+                );
+            }
+            if seen.get(index) {
+                bail!("Key {} is mapped to {}, which is already in use", i, index);
+            }
+            seen.set(index, true);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Remap> FuseMPH<T> {
+    /// Build a minimal perfect hash function on the given keys.
+    ///
+    /// Return an error if there are duplicate keys.
+    pub fn new(keys: &[T]) -> Result<Self> {
+        let mut seed = SEED;
+        for _ in 0..MAX_ATTEMPTS {
+            let sigs = keys
+                .iter()
+                .map(|key| T::remap(key, seed))
+                .collect::<Vec<_>>();
+            if let Some(mph) = Self::try_build(sigs, seed)? {
+                return Ok(mph);
+            }
+            seed = next_seed(seed);
+        }
+        bail!(
+            "Could not build the minimal perfect hash function after {} attempts",
+            MAX_ATTEMPTS
+        );
+    }
+
+    /// Try to build the function using the given signatures, returning
+    /// `None` if the hypergraph of some chunk cannot be peeled.
+    fn try_build(mut sigs: Vec<[u64; 2]>, seed: u64) -> Result<Option<Self>> {
+        let num_chunks = 1 << chunk_bits(sigs.len());
+        let chunk_mask = num_chunks as u64 - 1;
+        let (counts, cumul) = count_sort(&mut sigs, num_chunks, |sig| chunk(sig, chunk_mask));
+
+        let (l, segment_size) = fuse_parameters(*counts.iter().max().unwrap());
+        let num_vertices = segment_size * (l + 2);
+
+        let chunks = (0..num_chunks)
+            .map(|chunk| &sigs[cumul[chunk]..cumul[chunk + 1]])
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "rayon")]
+        let solutions = chunks
+            .par_iter()
+            .map(|sigs| Self::solve_chunk(sigs, l, segment_size))
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let solutions = chunks
+            .iter()
+            .map(|sigs| Self::solve_chunk(sigs, l, segment_size))
+            .collect::<Vec<_>>();
+
+        let mut values =
+            vec![0; (num_vertices * num_chunks + VALUES_PER_WORD - 1) / VALUES_PER_WORD];
+        for (chunk, solution) in solutions.into_iter().enumerate() {
+            let Some(chunk_values) = solution else {
+                if has_duplicates(chunks[chunk].iter().copied()) {
+                    bail!("Duplicate keys");
+                }
+                return Ok(None);
+            };
+            let chunk_offset = chunk * num_vertices;
+            for (v, &value) in chunk_values.iter().enumerate() {
+                let pos = chunk_offset + v;
+                values[pos / VALUES_PER_WORD] |= (value as usize) << (pos % VALUES_PER_WORD * 2);
+            }
+        }
+
+        let counts = values
+            .chunks(WORDS_PER_BLOCK)
+            .scan(0, |number_of_nonzeros, block| {
+                let count = *number_of_nonzeros;
+                *number_of_nonzeros += block.iter().map(|&w| nonzero_pairs(w)).sum::<usize>();
+                Some(count)
+            })
+            .collect();
+
+        Ok(Some(FuseMPH {
+            seed,
+            l,
+            num_keys: sigs.len(),
+            chunk_mask,
+            segment_size,
+            values,
+            counts,
+            _marker: core::marker::PhantomData,
+        }))
+    }
+
+    /// Peel the hypergraph of a chunk and assign 2-bit values to its
+    /// vertices, returning `None` if peeling fails.
+    ///
+    /// Hinges get a value in [1..3] (3 playing the role of 0), so that
+    /// they are exactly the nonzero vertices.
+    fn solve_chunk(sigs: &[[u64; 2]], l: usize, segment_size: usize) -> Option<Vec<u8>> {
+        let num_vertices = segment_size * (l + 2);
+        let mut stack = peel(sigs.len(), num_vertices, |e| {
+            edge(&sigs[e], l, segment_size)
+        })?;
+
+        let mut values = vec![0_u8; num_vertices];
+        while let Some((v, edge_index)) = stack.pop() {
+            let edge = edge(&sigs[edge_index], l, segment_size);
+            let hinge = edge.iter().position(|&x| x == v).unwrap();
+            let sum = edge
+                .iter()
+                .filter(|&&x| x != v)
+                .map(|&x| values[x] as usize)
+                .sum::<usize>();
+            values[v] = match (hinge + 6 - sum) % 3 {
+                0 => 3,
+                x => x as u8,
+            };
+        }
+        Some(values)
+    }
+}
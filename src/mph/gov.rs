@@ -9,16 +9,25 @@
 
 //! Ported from <https://github.com/vigna/Sux4J/blob/master/c/mph.c>
 
+use super::hypergraph::{orient, peel, solve_mod3};
 use super::spooky::{spooky_short, spooky_short_rehash};
-use anyhow::Result;
+use crate::bits::mmap_slice::MmapSlice;
+use anyhow::{bail, Result};
+use epserde::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Read;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
 
-/// A stub to load and access Genuzio-Ottaviano-Vigna minimal perfect hash functions.
+/// A structure to build, load and access Genuzio-Ottaviano-Vigna minimal perfect hash functions.
 ///
-/// To generate the structure you must use the Java version:
+/// The structure can be built natively using a [`GOVMPHBuilder`], and
+/// stored with the [`dump`](crate::mph::gov::GOVMPH::dump) method in the
+/// same format used by the Java version.
+///
+/// Alternatively, you can generate the structure using the Java version:
 /// ```bash
 /// java it.unimi.dsi.sux4j.mph.GOVMinimalPerfectHashFunction --byte-array SOURCE test.mph
 /// ```
@@ -30,42 +39,48 @@ use std::path::Path;
 /// echo '((it.unimi.dsi.sux4j.mph.GOVMinimalPerfectHashFunction)it.unimi.dsi.fastutil.io.BinIO.loadObject("test.mph")).dump("test.cmph");' | jshell
 /// ```
 ///
-/// You can now load the dumped file with the [`load`](crate::mph::gov::GOVMPH::load) method.
+/// You can now load the dumped file with the [`load`](crate::mph::gov::GOVMPH::load) method,
+/// or map it in memory without copying with the [`load_mmap`](crate::mph::gov::GOVMPH::load_mmap) method.
+///
+/// The structure is generic over the storage of its arrays, which can be
+/// any type implementing `AsRef<[u64]>`. It can also be serialized with
+/// ε-serde, and then mapped in memory with
+/// [`Deserialize::mmap`](epserde::des::Deserialize::mmap); the
+/// `gov_convert` example converts a dump in C-compatible format
+/// to ε-serde format.
 ///
 /// # Reference:
 /// - [Marco Genuzio, Giuseppe Ottaviano, and Sebastiano Vigna, Fast Scalable Construction of (Minimal Perfect Hash) Functions](https://arxiv.org/pdf/1603.04330.pdf)
 /// - [Java version with `dump` method](https://github.com/vigna/Sux4J/blob/master/src/it/unimi/dsi/sux4j/mph/GOVMinimalPerfectHashFunction.java)
-#[derive(Debug)]
-pub struct GOVMPH {
+#[derive(Epserde, Debug)]
+pub struct GOVMPH<D = Vec<u64>> {
     pub size: u64,
     pub multiplier: u64,
     pub global_seed: u64,
-    pub edge_offset_and_seed: Vec<u64>,
-    pub array: Vec<u64>,
+    pub edge_offset_and_seed: D,
+    pub array: D,
 }
 
-impl GOVMPH {
+impl<D: AsRef<[u64]>> GOVMPH<D> {
     pub fn size(&self) -> u64 {
         self.size
     }
 
     pub fn get_byte_array(&self, key: &[u8]) -> u64 {
         let signature = spooky_short(key, self.global_seed);
-        let bucket = ((((signature[0] as u128) >> 1) * (self.multiplier as u128)) >> 64) as u64;
-        let edge_offset_seed = self.edge_offset_and_seed[bucket as usize];
+        let bucket = signature_to_bucket(&signature, self.multiplier);
+        let edge_offset_and_seed = self.edge_offset_and_seed.as_ref();
+        let array = self.array.as_ref();
+        let edge_offset_seed = edge_offset_and_seed[bucket as usize];
         let bucket_offset = vertex_offset(edge_offset_seed);
         let num_variables =
-            vertex_offset(self.edge_offset_and_seed[bucket as usize + 1]) - bucket_offset;
+            vertex_offset(edge_offset_and_seed[bucket as usize + 1]) - bucket_offset;
         let e = signature_to_equation(&signature, edge_offset_seed & (!OFFSET_MASK), num_variables);
-        let eq_idx = (get_2bit_value(&self.array, e[0] + bucket_offset)
-            + get_2bit_value(&self.array, e[1] + bucket_offset)
-            + get_2bit_value(&self.array, e[2] + bucket_offset))
+        let eq_idx = (get_2bit_value(array, e[0] + bucket_offset)
+            + get_2bit_value(array, e[1] + bucket_offset)
+            + get_2bit_value(array, e[2] + bucket_offset))
             % 3;
-        let offset = count_nonzero_pairs(
-            bucket_offset,
-            bucket_offset + e[eq_idx as usize],
-            &self.array,
-        );
+        let offset = count_nonzero_pairs(bucket_offset, bucket_offset + e[eq_idx as usize], array);
         (edge_offset_seed & OFFSET_MASK) + offset
     }
 }
@@ -109,6 +124,16 @@ fn count_nonzero_pairs(start: u64, end: u64, array: &[u64]) -> u64 {
 
 const OFFSET_MASK: u64 = u64::MAX >> 8;
 const C_TIMES_256: u64 = 281; // floor((1.09 + 0.01) * 256.0)
+/// The expected number of keys in a bucket.
+const BUCKET_SIZE: u64 = 1500;
+/// The number of bits used to store the seed of a bucket.
+const SEED_BITS: u32 = 8;
+
+#[inline(always)]
+#[must_use]
+fn signature_to_bucket(signature: &[u64; 4], multiplier: u64) -> u64 {
+    ((((signature[0] as u128) >> 1) * (multiplier as u128)) >> 64) as u64
+}
 
 #[inline(always)]
 #[must_use]
@@ -155,25 +180,23 @@ impl GOVMPH {
         }
 
         macro_rules! read_array {
-            ($file:expr, $type:ty) => {{
-                // create a bytes buffer big enough for $len elements of type $type
+            ($file:expr) => {{
                 let len = read!($file, u64) as usize;
-                let bytes = len * core::mem::size_of::<$type>();
-                let mut buffer: Vec<u8> = vec![0; bytes];
-                // read the file in the buffer
-                $file.read_exact(&mut buffer)?;
-                // convert the buffer Vec<u8> into a Vec<$type>
-                let ptr = buffer.as_mut_ptr();
-                core::mem::forget(buffer);
-                unsafe { Vec::from_raw_parts(ptr as *mut $type, len, len) }
+                let mut array = vec![0_u64; len];
+                // read the file directly in the (properly aligned) array
+                $file.read_exact(bytemuck::cast_slice_mut(&mut array))?;
+                for word in array.iter_mut() {
+                    *word = u64::from_le(*word);
+                }
+                array
             }};
         }
         // actually lod the data :)
         let size = read!(file, u64);
         let multiplier = read!(file, u64);
         let global_seed = read!(file, u64);
-        let edge_offset_and_seed = read_array!(file, u64);
-        let array = read_array!(file, u64);
+        let edge_offset_and_seed = read_array!(file);
+        let array = read_array!(file);
 
         Ok(Self {
             size,
@@ -184,3 +207,241 @@ impl GOVMPH {
         })
     }
 }
+
+impl GOVMPH<MmapSlice> {
+    /// Given a path to a file `.cmph` generated from the java version,
+    /// map it in memory and access it without copying.
+    pub fn load_mmap<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let words = MmapSlice::new(path)?;
+        let header = words.as_ref();
+        if header.len() < 3 {
+            bail!("The file is too short to contain a GOVMPH structure");
+        }
+        let (edge_offset_and_seed, pos) = words.array(3)?;
+        let (array, _) = words.array(pos)?;
+
+        Ok(Self {
+            size: header[0],
+            multiplier: header[1],
+            global_seed: header[2],
+            edge_offset_and_seed,
+            array,
+        })
+    }
+}
+
+impl<D: AsRef<[u64]>> GOVMPH<D> {
+    /// Store this structure in a file, in the same format used by the `dump`
+    /// method of the Java version.
+    pub fn dump<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path.as_ref())?);
+        self.dump_writer(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Store this structure in a generic `Write` implementor, in the format
+    /// read by [`load_reader`](crate::mph::gov::GOVMPH::load_reader).
+    pub fn dump_writer<F: Write>(&self, mut file: F) -> Result<()> {
+        file.write_all(&self.size.to_le_bytes())?;
+        file.write_all(&self.multiplier.to_le_bytes())?;
+        file.write_all(&self.global_seed.to_le_bytes())?;
+        for array in [self.edge_offset_and_seed.as_ref(), self.array.as_ref()] {
+            file.write_all(&(array.len() as u64).to_le_bytes())?;
+            for word in array.iter() {
+                file.write_all(&word.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A builder for [`GOVMPH`].
+///
+/// Keys are divided into buckets of about 1500 elements, and for
+/// each bucket we look for a seed such that the associated 3-hypergraph
+/// can be oriented and the resulting system on GF(3) is solvable. Seeds are
+/// tried in increasing order starting from zero, the hypergraph is peeled
+/// depth-first, and the 2-core is solved by lazy Gaussian elimination,
+/// setting free variables to zero.
+///
+/// The resulting structure can be [dumped](GOVMPH::dump) in the format
+/// used by the Java version. However, the 2-core is oriented using
+/// augmenting paths, and not as in the Java version: for the same global
+/// seed, the offsets of the buckets are the same, but their seeds and the
+/// values of their vertices are in general different.
+///
+/// Buckets are solved in parallel if the `rayon` feature is enabled.
+///
+/// # Examples
+/// ```
+/// use sux::mph::gov::GOVMPHBuilder;
+///
+/// let keys = (0..1000).map(|i| format!("key{}", i)).collect::<Vec<_>>();
+/// let mph = GOVMPHBuilder::new(0).build(keys.iter()).unwrap();
+///
+/// let mut seen = vec![false; keys.len()];
+/// for key in &keys {
+///     let p = mph.get_byte_array(key.as_bytes()) as usize;
+///     assert!(!seen[p]);
+///     seen[p] = true;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct GOVMPHBuilder {
+    global_seed: u64,
+}
+
+impl GOVMPHBuilder {
+    /// Create a builder using the given global seed for hashing keys.
+    pub fn new(global_seed: u64) -> Self {
+        Self { global_seed }
+    }
+
+    /// Build a minimal perfect hash function for the given keys.
+    ///
+    /// Return an error if there are duplicate keys or if no seed makes
+    /// some bucket solvable.
+    pub fn build<K: AsRef<[u8]>, I: IntoIterator<Item = K>>(&self, keys: I) -> Result<GOVMPH> {
+        let global_seed = self.global_seed;
+        let mut signatures = keys
+            .into_iter()
+            .map(|key| spooky_short(key.as_ref(), global_seed))
+            .collect::<Vec<_>>();
+        let size = signatures.len() as u64;
+        let num_buckets = ((size + BUCKET_SIZE - 1) / BUCKET_SIZE).max(1);
+        let multiplier = num_buckets * 2;
+
+        signatures.sort_unstable_by_key(|sig| (signature_to_bucket(sig, multiplier), *sig));
+        if signatures.windows(2).any(|w| w[0] == w[1]) {
+            bail!("Duplicate keys");
+        }
+
+        // Edge offsets of the buckets
+        let mut edge_offset_and_seed = vec![0; num_buckets as usize + 1];
+        for sig in &signatures {
+            edge_offset_and_seed[signature_to_bucket(sig, multiplier) as usize + 1] += 1;
+        }
+        for b in 0..num_buckets as usize {
+            edge_offset_and_seed[b + 1] += edge_offset_and_seed[b];
+        }
+
+        let buckets = edge_offset_and_seed
+            .windows(2)
+            .map(|w| {
+                (
+                    &signatures[w[0] as usize..w[1] as usize],
+                    (vertex_offset(w[1]) - vertex_offset(w[0])) as usize,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "rayon")]
+        let solutions = buckets
+            .par_iter()
+            .map(|&(signatures, num_vertices)| solve_bucket(signatures, num_vertices))
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let solutions = buckets
+            .iter()
+            .map(|&(signatures, num_vertices)| solve_bucket(signatures, num_vertices))
+            .collect::<Vec<_>>();
+
+        let num_vertices = vertex_offset(size);
+        // We keep a spare word, as the Java version does
+        let mut array = vec![0_u64; (num_vertices as usize * 2 + 64) / 64];
+        for (b, solution) in solutions.into_iter().enumerate() {
+            let Some((seed, values)) = solution else {
+                bail!("Could not find a suitable seed for bucket {}", b);
+            };
+            let bucket_offset = vertex_offset(edge_offset_and_seed[b]) as usize;
+            for (v, &value) in values.iter().enumerate() {
+                let pos = (bucket_offset + v) * 2;
+                array[pos / 64] |= (value as u64) << (pos % 64);
+            }
+            edge_offset_and_seed[b] |= seed << (64 - SEED_BITS);
+        }
+
+        Ok(GOVMPH {
+            size,
+            multiplier,
+            global_seed,
+            edge_offset_and_seed,
+            array,
+        })
+    }
+}
+
+/// Find a seed for a bucket and the corresponding 2-bit values of its vertices.
+///
+/// Each edge must be assigned a distinct hinge vertex with a nonzero value,
+/// and the sum modulo 3 of the values of the vertices of the edge must be the
+/// index of the hinge in the edge, whereas all other vertices have value zero.
+fn solve_bucket(signatures: &[[u64; 4]], num_vertices: usize) -> Option<(u64, Vec<u8>)> {
+    if signatures.is_empty() {
+        return Some((0, vec![0; num_vertices]));
+    }
+
+    (0..1 << SEED_BITS).find_map(|seed: u64| {
+        let edges = signatures
+            .iter()
+            .map(|sig| {
+                signature_to_equation(sig, seed << (64 - SEED_BITS), num_vertices as u64)
+                    .map(|v| v as usize)
+            })
+            .collect::<Vec<_>>();
+
+        // Repeated vertices count as many times as they appear, so hinges
+        // of peeled edges appear exactly once in their edge.
+        let peeling = peel(num_vertices, edges.len(), |e| edges[e].iter().copied());
+        let core_hinges = orient(num_vertices, &edges, &peeling.core)?;
+
+        // Variables of the system are the hinges of the edges of the 2-core;
+        // all other vertices in the 2-core are set to zero.
+        let mut var = vec![usize::MAX; num_vertices];
+        for (j, &h) in core_hinges.iter().enumerate() {
+            var[h] = j;
+        }
+        let equations = peeling
+            .core
+            .iter()
+            .zip(core_hinges.iter())
+            .map(|(&e, &h)| {
+                let edge = &edges[e];
+                let coefficients = edge
+                    .iter()
+                    .filter(|&&v| var[v] != usize::MAX)
+                    .map(|&v| (var[v], 1))
+                    .collect::<Vec<_>>();
+                (coefficients, hinge_index(edge, h))
+            })
+            .collect::<Vec<_>>();
+        let solution = solve_mod3(core_hinges.len(), &equations)?;
+
+        // Values in [0..4): 3 is used for hinges whose value is zero modulo 3
+        let mut values = vec![0_u8; num_vertices];
+        for (&h, &x) in core_hinges.iter().zip(solution.iter()) {
+            values[h] = if x == 0 { 3 } else { x };
+        }
+
+        // Peeled edges are assigned in reverse peeling order
+        for &(e, h) in peeling.order.iter().rev() {
+            let edge = &edges[e];
+            let rest = edge
+                .iter()
+                .filter(|&&v| v != h)
+                .map(|&v| values[v] as usize)
+                .sum::<usize>();
+            let x = (hinge_index(edge, h) as usize + 3 - rest % 3) % 3;
+            values[h] = if x == 0 { 3 } else { x as u8 };
+        }
+
+        Some((seed, values))
+    })
+}
+
+/// Return the index of the first occurrence of a hinge in an edge.
+#[inline(always)]
+fn hinge_index(edge: &[usize; 3], h: usize) -> u8 {
+    edge.iter().position(|&v| v == h).unwrap() as u8
+}
@@ -0,0 +1,501 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Peeling and linear-system solving for 3-hypergraphs, as used in the
+//! construction of [GOV minimal perfect hash functions](crate::mph::gov::GOVMPH)
+//! and [static functions](crate::sf::gov3::GOV3).
+//!
+//! Edges may contain repeated vertices, whose multiplicity is taken into
+//! account when solving.
+
+/// Return the distinct vertices of an edge, in order of first appearance.
+#[inline(always)]
+pub(crate) fn distinct(edge: &[usize; 3]) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    edge.iter()
+        .enumerate()
+        .filter(|&(i, v)| !edge[..i].contains(v))
+        .map(|(_, &v)| v)
+}
+
+/// Return the number of occurrences of a vertex in an edge.
+#[inline(always)]
+pub(crate) fn multiplicity(edge: &[usize; 3], v: usize) -> usize {
+    edge.iter().filter(|&&x| x == v).count()
+}
+
+/// The result of peeling a 3-hypergraph.
+pub(crate) struct Peeling {
+    /// The peeled edges, in peeling order, with their hinges, that is,
+    /// the vertex of degree one that made it possible to peel them.
+    pub order: Vec<(usize, usize)>,
+    /// The edges of the 2-core, which cannot be peeled.
+    pub core: Vec<usize>,
+}
+
+/// Peel a 3-hypergraph with `num_vertices` vertices and `num_edges` edges,
+/// given a function returning the vertices of an edge.
+///
+/// Vertices are scanned in increasing order, and from each vertex of degree
+/// one we peel depth-first, pushing the vertices of a peeled edge in reverse
+/// order, as the Java version does. If `vertices` returns a vertex more than
+/// once, it counts as many incidences, so it cannot be the hinge of the edge.
+pub(crate) fn peel<I: DoubleEndedIterator<Item = usize>>(
+    num_vertices: usize,
+    num_edges: usize,
+    vertices: impl Fn(usize) -> I,
+) -> Peeling {
+    let mut degree = vec![0_u32; num_vertices];
+    // The xor of the indices of the edges incident to a vertex:
+    // when the degree is one, this is the only incident edge.
+    let mut edge_xor = vec![0_usize; num_vertices];
+    for e in 0..num_edges {
+        for v in vertices(e) {
+            degree[v] += 1;
+            edge_xor[v] ^= e;
+        }
+    }
+
+    let mut stack = Vec::new();
+    let mut peeled = vec![false; num_edges];
+    let mut order = Vec::with_capacity(num_edges);
+
+    for start in 0..num_vertices {
+        if degree[start] != 1 {
+            continue;
+        }
+        stack.push(start);
+        while let Some(v) = stack.pop() {
+            if degree[v] != 1 {
+                continue;
+            }
+            let e = edge_xor[v];
+            peeled[e] = true;
+            order.push((e, v));
+            for u in vertices(e) {
+                degree[u] -= 1;
+                edge_xor[u] ^= e;
+            }
+            for u in vertices(e).rev() {
+                if degree[u] == 1 {
+                    stack.push(u);
+                }
+            }
+        }
+    }
+
+    let core = (0..num_edges).filter(|&e| !peeled[e]).collect();
+    Peeling { order, core }
+}
+
+/// Assign to each edge of `subset` a distinct vertex (its hinge) using
+/// augmenting paths, returning `None` if no such assignment exists.
+pub(crate) fn orient(
+    num_vertices: usize,
+    edges: &[[usize; 3]],
+    subset: &[usize],
+) -> Option<Vec<usize>> {
+    fn augment(
+        e: usize,
+        edges: &[[usize; 3]],
+        matched: &mut [usize],
+        visited: &mut [usize],
+        stamp: usize,
+    ) -> bool {
+        for v in distinct(&edges[e]) {
+            if visited[v] == stamp {
+                continue;
+            }
+            visited[v] = stamp;
+            if matched[v] == usize::MAX || augment(matched[v], edges, matched, visited, stamp) {
+                matched[v] = e;
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut matched = vec![usize::MAX; num_vertices];
+    let mut visited = vec![usize::MAX; num_vertices];
+    for (stamp, &e) in subset.iter().enumerate() {
+        if !augment(e, edges, &mut matched, &mut visited, stamp) {
+            return None;
+        }
+    }
+
+    let mut hinges = vec![0; edges.len()];
+    for (v, &e) in matched.iter().enumerate() {
+        if e != usize::MAX {
+            hinges[e] = v;
+        }
+    }
+    Some(subset.iter().map(|&e| hinges[e]).collect())
+}
+
+/// Solve a linear system on GF(3) with `num_vars` variables using lazy
+/// Gaussian elimination.
+///
+/// Each equation is given by a list of pairs (variable, coefficient), where
+/// coefficients are in [0..3) and variables might be repeated, and by a
+/// constant term in [0..3). Free variables are set to zero. Return `None` if
+/// the system is not solvable.
+///
+/// The elimination strategy is the same as that of [`solve_mod2`].
+pub(crate) fn solve_mod3(num_vars: usize, equations: &[(Vec<(usize, u8)>, u8)]) -> Option<Vec<u8>> {
+    let num_words = (num_vars + 63) / 64;
+    let num_equations = equations.len();
+
+    // Rows are bitsliced: the bit of `ones` is set for coefficients equal
+    // to one, the bit of `twos` for coefficients equal to two.
+    #[derive(Default)]
+    struct Row {
+        ones: Vec<u64>,
+        twos: Vec<u64>,
+        c: u8,
+    }
+
+    impl Row {
+        #[inline(always)]
+        fn get(&self, j: usize) -> u8 {
+            ((self.ones[j / 64] >> (j % 64)) & 1 | ((self.twos[j / 64] >> (j % 64)) & 1) << 1) as u8
+        }
+
+        fn negate(&mut self) {
+            core::mem::swap(&mut self.ones, &mut self.twos);
+            self.c = (3 - self.c) % 3;
+        }
+
+        /// Subtract `a` times `other` from this row.
+        fn sub(&mut self, other: &Row, a: u8) {
+            // Subtracting twice a row is the same as adding it
+            let (y_ones, y_twos) = if a == 1 {
+                (&other.twos, &other.ones)
+            } else {
+                (&other.ones, &other.twos)
+            };
+            for w in 0..self.ones.len() {
+                let (x1, x2) = (self.ones[w], self.twos[w]);
+                let (y1, y2) = (y_ones[w], y_twos[w]);
+                let t = (x1 | y2) ^ (x2 | y1);
+                self.ones[w] = (x2 | y2) ^ t;
+                self.twos[w] = (x1 | y1) ^ t;
+            }
+            self.c = (self.c + 6 - a * other.c) % 3;
+        }
+
+        /// Return the first variable with a nonzero coefficient.
+        fn first(&self) -> Option<usize> {
+            self.ones
+                .iter()
+                .zip(self.twos.iter())
+                .position(|(&x, &y)| x | y != 0)
+                .map(|i| i * 64 + (self.ones[i] | self.twos[i]).trailing_zeros() as usize)
+        }
+
+        /// Return the constant term minus the values of the variables of the
+        /// row different from `skip`, multiplied by their coefficients.
+        fn evaluate(&self, values: &[u8], skip: usize) -> u8 {
+            let mut result = self.c as usize;
+            for (i, (&x, &y)) in self.ones.iter().zip(self.twos.iter()).enumerate() {
+                let mut word = x | y;
+                while word != 0 {
+                    let j = i * 64 + word.trailing_zeros() as usize;
+                    if j != skip {
+                        result += 6 - self.get(j) as usize * values[j] as usize;
+                    }
+                    word &= word - 1;
+                }
+            }
+            (result % 3) as u8
+        }
+    }
+
+    let mut var_to_eqs = vec![vec![]; num_vars];
+    let mut priority = vec![0_usize; num_equations];
+    let mut rows = Vec::with_capacity(num_equations);
+    for (e, (coefficients, c)) in equations.iter().enumerate() {
+        let mut row = Row {
+            ones: vec![0; num_words],
+            twos: vec![0; num_words],
+            c: *c,
+        };
+        for &(v, a) in coefficients {
+            let a = (row.get(v) + a) % 3;
+            row.ones[v / 64] &= !(1 << (v % 64));
+            row.twos[v / 64] &= !(1 << (v % 64));
+            match a {
+                1 => row.ones[v / 64] |= 1 << (v % 64),
+                2 => row.twos[v / 64] |= 1 << (v % 64),
+                _ => {}
+            }
+        }
+        for &(v, _) in coefficients {
+            // Count each variable once, and only if it did not cancel out
+            if row.get(v) != 0 && var_to_eqs[v].last() != Some(&e) {
+                var_to_eqs[v].push(e);
+                priority[e] += 1;
+            }
+        }
+        rows.push(row);
+    }
+
+    let mut idle = vec![u64::MAX; num_words];
+    let mut by_weight = (0..num_vars).collect::<Vec<_>>();
+    by_weight.sort_unstable_by_key(|&v| core::cmp::Reverse(var_to_eqs[v].len()));
+    let mut next_active = 0;
+
+    let mut stack = (0..num_equations)
+        .filter(|&e| priority[e] <= 1)
+        .collect::<Vec<_>>();
+    let mut solved = Vec::new();
+    let mut dense = Vec::new();
+    let mut remaining = num_equations;
+
+    while remaining > 0 {
+        if let Some(e) = stack.pop() {
+            remaining -= 1;
+            if priority[e] == 0 {
+                dense.push(e);
+                continue;
+            }
+            // Exactly one idle variable: use the equation as its pivot
+            let (i, word) = rows[e]
+                .ones
+                .iter()
+                .zip(rows[e].twos.iter())
+                .zip(idle.iter())
+                .map(|((&x, &y), &z)| (x | y) & z)
+                .enumerate()
+                .find(|&(_, w)| w != 0)
+                .unwrap();
+            let v = i * 64 + word.trailing_zeros() as usize;
+            idle[v / 64] &= !(1 << (v % 64));
+            solved.push((e, v));
+
+            let mut pivot = core::mem::take(&mut rows[e]);
+            if pivot.get(v) == 2 {
+                pivot.negate();
+            }
+            for &f in &var_to_eqs[v] {
+                if f != e {
+                    let a = rows[f].get(v);
+                    if a != 0 {
+                        rows[f].sub(&pivot, a);
+                        priority[f] -= 1;
+                        if priority[f] == 1 {
+                            stack.push(f);
+                        }
+                    }
+                }
+            }
+            rows[e] = pivot;
+        } else {
+            // Make active the heaviest idle variable
+            while (idle[by_weight[next_active] / 64] >> (by_weight[next_active] % 64)) & 1 == 0 {
+                next_active += 1;
+            }
+            let v = by_weight[next_active];
+            idle[v / 64] &= !(1 << (v % 64));
+            for &f in &var_to_eqs[v] {
+                if rows[f].get(v) != 0 {
+                    priority[f] -= 1;
+                    if priority[f] == 1 {
+                        stack.push(f);
+                    }
+                }
+            }
+        }
+    }
+
+    // Gaussian elimination on the dense system
+    let mut pivots: Vec<(usize, usize)> = Vec::new();
+    for &e in &dense {
+        let mut row = core::mem::take(&mut rows[e]);
+        for &(p, col) in &pivots {
+            let a = row.get(col);
+            if a != 0 {
+                row.sub(&rows[p], a);
+            }
+        }
+        match row.first() {
+            None => {
+                if row.c != 0 {
+                    return None;
+                }
+            }
+            Some(col) => {
+                if row.get(col) == 2 {
+                    row.negate();
+                }
+                pivots.push((e, col));
+            }
+        }
+        rows[e] = row;
+    }
+
+    let mut values = vec![0_u8; num_vars];
+    for &(e, col) in pivots.iter().rev().chain(solved.iter().rev()) {
+        values[col] = rows[e].evaluate(&values, col);
+    }
+    Some(values)
+}
+
+/// Solve a linear system on GF(2) with `num_vars` variables using lazy
+/// Gaussian elimination.
+///
+/// Each equation is given by a list of variables, which cancel out if
+/// repeated an even number of times, and by a constant term, which is a
+/// vector of 64 bits. Free variables are set to zero. Return `None` if the
+/// system is not solvable.
+///
+/// Variables are initially idle. Equations with a single idle variable are
+/// used to eliminate that variable from all other equations; when there are
+/// none, the idle variable appearing in most equations is made active.
+/// Equations with no idle variables form a dense system on the active
+/// variables, which is solved by standard Gaussian elimination.
+///
+/// # Reference:
+/// - [Marco Genuzio, Giuseppe Ottaviano, and Sebastiano Vigna, Fast Scalable Construction of (Minimal Perfect Hash) Functions](https://arxiv.org/pdf/1603.04330.pdf)
+pub(crate) fn solve_mod2(num_vars: usize, equations: &[(Vec<usize>, u64)]) -> Option<Vec<u64>> {
+    let num_words = (num_vars + 63) / 64;
+    let num_equations = equations.len();
+
+    #[inline(always)]
+    fn get(row: &[u64], j: usize) -> bool {
+        (row[j / 64] >> (j % 64)) & 1 != 0
+    }
+
+    #[inline(always)]
+    fn xor(row: &mut [u64], other: &[u64]) {
+        for (x, &y) in row.iter_mut().zip(other.iter()) {
+            *x ^= y;
+        }
+    }
+
+    /// Return the constant term xored with the values of the variables of
+    /// the row different from `skip`.
+    fn evaluate(row: &[u64], c: u64, values: &[u64], skip: usize) -> u64 {
+        let mut result = c;
+        for (i, &word) in row.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let j = i * 64 + word.trailing_zeros() as usize;
+                if j != skip {
+                    result ^= values[j];
+                }
+                word &= word - 1;
+            }
+        }
+        result
+    }
+
+    let mut rows = vec![vec![0_u64; num_words]; num_equations];
+    let mut c = Vec::with_capacity(num_equations);
+    let mut var_to_eqs = vec![vec![]; num_vars];
+    let mut priority = vec![0_usize; num_equations];
+    for (e, (row, (vars, rhs))) in rows.iter_mut().zip(equations.iter()).enumerate() {
+        for &v in vars {
+            row[v / 64] ^= 1 << (v % 64);
+        }
+        for &v in vars {
+            // Count each variable once, and only if it did not cancel out
+            if get(row, v) && var_to_eqs[v].last() != Some(&e) {
+                var_to_eqs[v].push(e);
+                priority[e] += 1;
+            }
+        }
+        c.push(*rhs);
+    }
+
+    let mut idle = vec![u64::MAX; num_words];
+    let mut by_weight = (0..num_vars).collect::<Vec<_>>();
+    by_weight.sort_unstable_by_key(|&v| core::cmp::Reverse(var_to_eqs[v].len()));
+    let mut next_active = 0;
+
+    let mut stack = (0..num_equations)
+        .filter(|&e| priority[e] <= 1)
+        .collect::<Vec<_>>();
+    let mut solved = Vec::new();
+    let mut dense = Vec::new();
+    let mut remaining = num_equations;
+
+    while remaining > 0 {
+        if let Some(e) = stack.pop() {
+            remaining -= 1;
+            if priority[e] == 0 {
+                dense.push(e);
+                continue;
+            }
+            // Exactly one idle variable: use the equation as its pivot
+            let (i, word) = rows[e]
+                .iter()
+                .zip(idle.iter())
+                .map(|(&x, &y)| x & y)
+                .enumerate()
+                .find(|&(_, w)| w != 0)
+                .unwrap();
+            let v = i * 64 + word.trailing_zeros() as usize;
+            idle[v / 64] &= !(1 << (v % 64));
+            solved.push((e, v));
+
+            let pivot = core::mem::take(&mut rows[e]);
+            for &f in &var_to_eqs[v] {
+                if f != e && get(&rows[f], v) {
+                    xor(&mut rows[f], &pivot);
+                    c[f] ^= c[e];
+                    priority[f] -= 1;
+                    if priority[f] == 1 {
+                        stack.push(f);
+                    }
+                }
+            }
+            rows[e] = pivot;
+        } else {
+            // Make active the heaviest idle variable
+            while !get(&idle, by_weight[next_active]) {
+                next_active += 1;
+            }
+            let v = by_weight[next_active];
+            idle[v / 64] &= !(1 << (v % 64));
+            for &f in &var_to_eqs[v] {
+                if get(&rows[f], v) {
+                    priority[f] -= 1;
+                    if priority[f] == 1 {
+                        stack.push(f);
+                    }
+                }
+            }
+        }
+    }
+
+    // Gaussian elimination on the dense system
+    let mut pivots: Vec<(usize, usize)> = Vec::new();
+    for &e in &dense {
+        let mut row = core::mem::take(&mut rows[e]);
+        for &(p, col) in &pivots {
+            if get(&row, col) {
+                xor(&mut row, &rows[p]);
+                c[e] ^= c[p];
+            }
+        }
+        match row.iter().position(|&w| w != 0) {
+            None => {
+                if c[e] != 0 {
+                    return None;
+                }
+            }
+            Some(i) => pivots.push((e, i * 64 + row[i].trailing_zeros() as usize)),
+        }
+        rows[e] = row;
+    }
+
+    let mut values = vec![0_u64; num_vars];
+    for &(e, col) in pivots.iter().rev().chain(solved.iter().rev()) {
+        values[col] = evaluate(&rows[e], c[e], &values, col);
+    }
+    Some(values)
+}
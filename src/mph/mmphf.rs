@@ -0,0 +1,165 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Monotone minimal perfect hash functions.
+
+use crate::prelude::*;
+use crate::sf::fuse::{Function, Remap};
+use anyhow::{bail, Result};
+use epserde::*;
+
+/// Return the `i`-th byte of a key followed by a NUL terminator.
+#[inline(always)]
+fn byte(key: &[u8], i: usize) -> u8 {
+    key.get(i).copied().unwrap_or(0)
+}
+
+/// Return the length in bits of the longest common prefix of two
+/// NUL-terminated keys.
+fn lcp_bits(a: &[u8], b: &[u8]) -> usize {
+    let len = a.len().min(b.len()) + 1;
+    (0..len)
+        .find(|&i| byte(a, i) != byte(b, i))
+        .map_or(len * 8, |i| {
+            i * 8 + (byte(a, i) ^ byte(b, i)).leading_zeros() as usize
+        })
+}
+
+/// Return a byte representation of the first `lcp` bits of a
+/// NUL-terminated key, followed by `lcp` itself, so that prefixes of
+/// different lengths are distinct.
+fn prefix(key: &[u8], lcp: usize) -> Vec<u8> {
+    let mut prefix = (0..(lcp + 7) / 8).map(|i| byte(key, i)).collect::<Vec<_>>();
+    if lcp % 8 != 0 {
+        *prefix.last_mut().unwrap() &= !(0xFF >> (lcp % 8));
+    }
+    prefix.extend_from_slice(&lcp.to_le_bytes());
+    prefix
+}
+
+/// Return the number of bits necessary to represent `x`.
+fn bit_width(x: usize) -> usize {
+    (usize::BITS - x.leading_zeros()) as usize
+}
+
+/// A monotone minimal perfect hash function based on longest common
+/// prefixes.
+///
+/// Given a lexicographically sorted set of byte strings, the function maps
+/// each key to its rank in the set, without storing the keys.
+/// Keys are divided in buckets of consecutive keys whose size is a power
+/// of two. Keys are seen as bit strings followed by a NUL terminator, which
+/// makes them prefix free; thus, the longest common prefixes of the buckets
+/// are distinct. We store three [static functions](Function): the first
+/// maps each key to its offset in its bucket, the second maps each key to
+/// the length of the longest common prefix of its bucket, and the third
+/// maps the longest common prefix of each bucket to the index of the bucket.
+///
+/// Keys must not contain NUL bytes.
+///
+/// # Examples
+/// ```
+/// use sux::mph::mmphf::LcpMMPH;
+///
+/// let keys = ["apple", "banana", "cherry", "date", "elderberry"]
+///     .map(String::from);
+/// let mmph = LcpMMPH::new(&keys).unwrap();
+///
+/// for (i, key) in keys.iter().enumerate() {
+///     assert_eq!(mmph.get(key), i);
+/// }
+/// ```
+///
+/// # Reference:
+/// - [Djamal Belazzougui, Paolo Boldi, Rasmus Pagh, and Sebastiano Vigna, Monotone Minimal Perfect Hashing: Searching a Sorted Table with O(1) Accesses](https://vigna.di.unimi.it/papers.php#BBPMMPH)
+#[derive(Epserde, Debug, Clone)]
+pub struct LcpMMPH<T, S = CompactArray<Vec<usize>>> {
+    log2_bucket_size: usize,
+    num_keys: usize,
+    offsets: Function<T, S>,
+    lcps: Function<T, S>,
+    buckets: Function<Vec<u8>, S>,
+}
+
+impl<T, S> LcpMMPH<T, S> {
+    /// Return the number of keys of the function.
+    pub fn len(&self) -> usize {
+        self.num_keys
+    }
+
+    /// Return whether the function has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Remap + AsRef<[u8]>, S: VSlice> LcpMMPH<T, S> {
+    /// Return the lexicographical rank of a key.
+    ///
+    /// If the key was not used to build the function, the result is
+    /// arbitrary.
+    pub fn get(&self, key: &T) -> usize {
+        let lcp = self.lcps.get(key);
+        let bucket = self.buckets.get(&prefix(key.as_ref(), lcp));
+        (bucket << self.log2_bucket_size) + self.offsets.get(key)
+    }
+}
+
+impl<T: Remap + AsRef<[u8]>> LcpMMPH<T> {
+    /// Build a monotone minimal perfect hash function on the given keys.
+    ///
+    /// Return an error if the keys are not strictly increasing in
+    /// lexicographical order, or if some key contains a NUL byte.
+    pub fn new(keys: &[T]) -> Result<Self> {
+        if let Some(i) = keys.iter().position(|key| key.as_ref().contains(&0)) {
+            bail!("Key {} contains a NUL byte", i);
+        }
+        if let Some(i) = keys.windows(2).position(|w| w[0].as_ref() >= w[1].as_ref()) {
+            bail!("Keys {} and {} are not strictly increasing", i, i + 1);
+        }
+
+        let num_keys = keys.len();
+        // Buckets of about ln n keys
+        let log2_bucket_size = (num_keys as f64).ln().max(1.0).log2().ceil() as usize;
+        let bucket_size = 1 << log2_bucket_size;
+
+        let mut lcps = Vec::with_capacity(num_keys);
+        let mut prefixes = Vec::with_capacity((num_keys + bucket_size - 1) / bucket_size);
+        for bucket in keys.chunks(bucket_size) {
+            let lcp = bucket
+                .windows(2)
+                .map(|w| lcp_bits(w[0].as_ref(), w[1].as_ref()))
+                .min()
+                // A bucket with a single key uses the whole key
+                .unwrap_or((bucket[0].as_ref().len() + 1) * 8);
+            lcps.resize(lcps.len() + bucket.len(), lcp);
+            prefixes.push(prefix(bucket[0].as_ref(), lcp));
+        }
+
+        let offsets = (0..num_keys)
+            .map(|i| i & (bucket_size - 1))
+            .collect::<Vec<_>>();
+        let bucket_indices = (0..prefixes.len()).collect::<Vec<_>>();
+
+        Ok(Self {
+            log2_bucket_size,
+            num_keys,
+            offsets: Function::new(keys, &offsets, log2_bucket_size)?,
+            lcps: Function::new(
+                keys,
+                &lcps,
+                bit_width(lcps.iter().copied().max().unwrap_or(0)),
+            )?,
+            buckets: Function::new(
+                &prefixes,
+                &bucket_indices,
+                bit_width(prefixes.len().saturating_sub(1)),
+            )?,
+        })
+    }
+}
@@ -9,5 +9,8 @@
 
 //! Minimal perfect hash functions.
 
+pub mod fuse_mph;
 pub mod gov;
+pub(crate) mod hypergraph;
+pub mod mmphf;
 pub mod spooky;
@@ -13,7 +13,15 @@ pub use quantum_index::*;
 mod quantum_zero_index;
 pub use quantum_zero_index::*;
 
+mod rank9;
+pub use rank9::*;
+
+mod simple_select;
+pub use simple_select::*;
+
 pub mod prelude {
     pub use super::quantum_index::QuantumIndex;
     pub use super::quantum_zero_index::*;
+    pub use super::rank9::*;
+    pub use super::simple_select::*;
 }
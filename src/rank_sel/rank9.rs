@@ -0,0 +1,200 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::prelude::*;
+use anyhow::Result;
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+/// The number of words in a basic block.
+const WORDS_PER_BLOCK: usize = 8;
+
+/// A ranking structure using 25% of additional space and providing
+/// constant-time ranking.
+///
+/// The underlying bit vector is divided in basic blocks of 512 bits.
+/// For each basic block we record, in a pair of words, the number of ones
+/// preceding the block and, packed in 9-bit fields, the number of ones
+/// preceding each of the remaining seven words of the block, counted
+/// from the start of the block. A rank thus requires reading two words of
+/// counts and a word of the bit vector.
+///
+/// The index takes a backend parameter `B` that can be any type that implements
+/// [`BitLength`] and `AsRef<[usize]>`, such as [`BitVec`](crate::bits::bit_vec::BitVec)
+/// or [`CountBitVec`](crate::bits::bit_vec::CountBitVec). It is built using
+/// [`ConvertTo`], and it forwards [`Select`], [`SelectHinted`], [`SelectZero`],
+/// and [`SelectZeroHinted`] to the backend, if available.
+///
+/// Bits of the underlying storage after the [length](BitLength::len) of
+/// the bit vector must be zero.
+///
+/// # Reference:
+/// - [Sebastiano Vigna, Broadword Implementation of Rank/Select Queries](https://vigna.di.unimi.it/papers.php#VigBIRSQ)
+#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rank9<B = crate::bits::bit_vec::CountBitVec, C = Vec<usize>> {
+    bits: B,
+    counts: C,
+}
+
+impl<B, C> Rank9<B, C> {
+    /// # Safety
+    /// `counts` must contain the counts of the bit vector `bits`.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(bits: B, counts: C) -> Self {
+        Self { bits, counts }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> (B, C) {
+        (self.bits, self.counts)
+    }
+}
+
+impl<B: BitLength + AsRef<[usize]>> Rank9<B, Vec<usize>> {
+    fn build_counts(bits: &B) -> Vec<usize> {
+        let words = bits.as_ref();
+        let num_words = (bits.len() + BITS - 1) / BITS;
+        // We need an additional block to rank the last position when
+        // the number of words is a multiple of the block size.
+        let num_blocks = num_words / WORDS_PER_BLOCK + 1;
+        let mut counts = vec![0; num_blocks * 2];
+
+        let mut number_of_ones = 0;
+        for (block, block_words) in words[..num_words].chunks(WORDS_PER_BLOCK).enumerate() {
+            counts[block * 2] = number_of_ones;
+            let mut ones_in_block = 0;
+            for j in 1..WORDS_PER_BLOCK {
+                ones_in_block += block_words
+                    .get(j - 1)
+                    .map_or(0, |w| w.count_ones() as usize);
+                counts[block * 2 + 1] |= ones_in_block << (9 * (j - 1));
+            }
+            number_of_ones += block_words
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>();
+        }
+
+        if num_words % WORDS_PER_BLOCK == 0 {
+            counts[(num_blocks - 1) * 2] = number_of_ones;
+        }
+        counts
+    }
+}
+
+impl<B: BitLength, C> BitLength for Rank9<B, C> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+/// The number of ones is computed using the counts.
+impl<B: BitLength + AsRef<[usize]>, C: AsRef<[usize]>> BitCount for Rank9<B, C> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        unsafe { self.rank_unchecked(self.bits.len()) }
+    }
+}
+
+impl<B: BitLength + AsRef<[usize]>, C: AsRef<[usize]>> Rank for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let word = pos / BITS;
+        let block = (word / WORDS_PER_BLOCK) * 2;
+        let offset = (word % WORDS_PER_BLOCK) as isize - 1;
+        let counts = self.counts.as_ref();
+
+        // When offset is -1, the shift is by 63 bits, and returns
+        // the unused (and thus zero) most significant bit.
+        let mut rank = *counts.get_unchecked(block)
+            + (*counts.get_unchecked(block + 1) >> ((offset + ((offset >> 60) & 8)) * 9) & 0x1FF);
+
+        let bit = pos % BITS;
+        // Avoid reading past the end when the length is a multiple of the word size
+        if bit != 0 {
+            rank +=
+                (*self.bits.as_ref().get_unchecked(word) & ((1 << bit) - 1)).count_ones() as usize;
+        }
+        rank
+    }
+}
+
+impl<B: BitLength + AsRef<[usize]>, C: AsRef<[usize]>> RankZero for Rank9<B, C> {}
+
+/// If the underlying implementation has select, forward the methods.
+impl<B: Select + BitLength + AsRef<[usize]>, C: AsRef<[usize]>> Select for Rank9<B, C> {
+    #[inline(always)]
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.bits.select(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_unchecked(rank)
+    }
+}
+
+/// If the underlying implementation has a hint for select, forward the methods.
+impl<B: SelectHinted + BitLength + AsRef<[usize]>, C: AsRef<[usize]>> SelectHinted for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn select_unchecked_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
+        self.bits.select_unchecked_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// If the underlying implementation has select zero, forward the methods.
+impl<B: SelectZero + AsRef<[usize]>, C: AsRef<[usize]>> SelectZero for Rank9<B, C> {
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// If the underlying implementation has a hint for select zero, forward the methods.
+impl<B: SelectZeroHinted + AsRef<[usize]>, C: AsRef<[usize]>> SelectZeroHinted for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn select_zero_unchecked_hinted(
+        &self,
+        rank: usize,
+        pos: usize,
+        rank_at_pos: usize,
+    ) -> usize {
+        self.bits
+            .select_zero_unchecked_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forget the index.
+impl<B> ConvertTo<B> for Rank9<B, Vec<usize>> {
+    #[inline(always)]
+    fn convert_to(self) -> Result<B> {
+        Ok(self.bits)
+    }
+}
+
+/// Create and add a ranking structure.
+impl<B: BitLength + AsRef<[usize]>> ConvertTo<Rank9<B, Vec<usize>>> for B {
+    #[inline(always)]
+    fn convert_to(self) -> Result<Rank9<B, Vec<usize>>> {
+        Ok(Rank9 {
+            counts: Rank9::build_counts(&self),
+            bits: self,
+        })
+    }
+}
+
+impl<B: AsRef<[usize]>, C> AsRef<[usize]> for Rank9<B, C> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
@@ -0,0 +1,327 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::prelude::*;
+use anyhow::Result;
+use common_traits::SelectInWord;
+use epserde::*;
+
+const BITS: usize = usize::BITS as usize;
+/// The target average span in bits of an inventory block.
+const TARGET_INVENTORY_SPAN: usize = 8192;
+/// The base-2 logarithm of the maximum number of subinventory entries
+/// for each inventory entry.
+const LOG2_MAX_SUBS_PER_INVENTORY: usize = 4;
+/// The maximum span in bits of an inventory block whose ones are
+/// located using 16-bit offsets.
+const MAX_SPAN: usize = 1 << 16;
+/// The flag marking inventory entries pointing into the spill.
+const SPILL_FLAG: usize = 1 << (BITS - 1);
+
+/// A selection structure based on a two-level inventory that bounds
+/// the number of words read by each selection.
+///
+/// The structure records the position of a one every <var>q</var>, where
+/// <var>q</var> is a power of two chosen so that the average distance between
+/// recorded ones is a few thousand bits. Every <var>q</var> ones form an
+/// inventory block: if the block spans at most 2<sup>16</sup> bits, the
+/// position of every <var>q</var>/16-th one in the block is recorded in a
+/// subinventory using a 16-bit offset, and a selection reads at most
+/// 2<sup>10</sup> + 1 words of the bit vector (but usually just a few); otherwise, the
+/// position of all ones in the block is recorded explicitly in a spill
+/// and no word of the bit vector is read at all.
+///
+/// The index takes a backend parameter `B` that can be any type that implements
+/// [`BitLength`], [`BitCount`], and `AsRef<[usize]>`. This will usually be something
+/// like [`CountBitVec`](crate::bits::bit_vec::CountBitVec), or possibly
+/// a [`CountBitVec`](crate::bits::bit_vec::CountBitVec) wrapped in another index structure for which
+/// this structure has delegation (e.g., [`Rank9`](crate::rank_sel::Rank9) or
+/// [`QuantumZeroIndex`](crate::rank_sel::QuantumZeroIndex)). The index is built
+/// using [`ConvertTo`]. See the documentation of [`EliasFano`](crate::dict::elias_fano::EliasFano)
+/// for an example of this approach.
+///
+/// Bits of the underlying storage after the [length](BitLength::len) of
+/// the bit vector must be zero.
+///
+/// # Reference:
+/// - [Sebastiano Vigna, Broadword Implementation of Rank/Select Queries](https://vigna.di.unimi.it/papers.php#VigBIRSQ)
+/// - [Java version](https://github.com/vigna/Sux4J/blob/master/src/it/unimi/dsi/sux4j/bits/SimpleSelect.java)
+#[derive(Epserde, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleSelect<B, I = Vec<usize>, S = Vec<u16>> {
+    bits: B,
+    /// The position of every 2<sup>`log2_ones_per_inventory`</sup>-th one,
+    /// or, if the most significant bit is set, the position in `spill` of
+    /// the positions of the ones of the block.
+    inventory: I,
+    /// The offset with respect to the inventory of every
+    /// 2<sup>`log2_ones_per_sub`</sup>-th one.
+    subinventory: S,
+    /// The explicit position of the ones in blocks with a large span.
+    spill: I,
+    log2_ones_per_inventory: usize,
+    log2_ones_per_sub: usize,
+}
+
+impl<B, I, S> SimpleSelect<B, I, S> {
+    /// # Safety
+    /// The inventories and the spill must have been built for `bits`
+    /// with the given parameters.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(
+        bits: B,
+        inventory: I,
+        subinventory: S,
+        spill: I,
+        log2_ones_per_inventory: usize,
+        log2_ones_per_sub: usize,
+    ) -> Self {
+        Self {
+            bits,
+            inventory,
+            subinventory,
+            spill,
+            log2_ones_per_inventory,
+            log2_ones_per_sub,
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> (B, I, S, I, usize, usize) {
+        (
+            self.bits,
+            self.inventory,
+            self.subinventory,
+            self.spill,
+            self.log2_ones_per_inventory,
+            self.log2_ones_per_sub,
+        )
+    }
+}
+
+impl<B: BitLength + BitCount + AsRef<[usize]>> SimpleSelect<B, Vec<usize>, Vec<u16>> {
+    fn new(bits: B) -> Self {
+        let len = bits.len();
+        let count = bits.count();
+        let words = &bits.as_ref()[..(len + BITS - 1) / BITS];
+
+        let log2_ones_per_inventory = if count == 0 {
+            0
+        } else {
+            ((count * TARGET_INVENTORY_SPAN + len - 1) / len)
+                .max(1)
+                .next_power_of_two()
+                .trailing_zeros() as usize
+        };
+        let log2_ones_per_sub = log2_ones_per_inventory.saturating_sub(LOG2_MAX_SUBS_PER_INVENTORY);
+        let log2_subs_per_inventory = log2_ones_per_inventory - log2_ones_per_sub;
+        let ones_per_inventory = 1 << log2_ones_per_inventory;
+        let inventory_size = (count + ones_per_inventory - 1) >> log2_ones_per_inventory;
+
+        // Record the position of every 2^log2_ones_per_sub-th one
+        let mut samples: Vec<usize> = Vec::with_capacity(inventory_size << log2_subs_per_inventory);
+        let mut number_of_ones = 0;
+        let mut next_sample = 0;
+        for (i, &word) in words.iter().enumerate() {
+            let ones_in_word = word.count_ones() as usize;
+            while number_of_ones + ones_in_word > next_sample {
+                samples.push(i * BITS + word.select_in_word(next_sample - number_of_ones));
+                next_sample += 1 << log2_ones_per_sub;
+            }
+            number_of_ones += ones_in_word;
+        }
+
+        let mut inventory = Vec::with_capacity(inventory_size);
+        let mut subinventory = vec![0; inventory_size << log2_subs_per_inventory];
+        let mut spill = Vec::new();
+
+        for i in 0..inventory_size {
+            let first_sample = i << log2_subs_per_inventory;
+            let start = samples[first_sample];
+            let next_first_sample = first_sample + (1 << log2_subs_per_inventory);
+            let end = if next_first_sample < samples.len() {
+                samples[next_first_sample]
+            } else {
+                len
+            };
+
+            // With a single one per block the only offset is zero
+            if end - start <= MAX_SPAN || log2_ones_per_inventory == 0 {
+                inventory.push(start);
+                for (j, &sample) in samples[first_sample..]
+                    .iter()
+                    .take(1 << log2_subs_per_inventory)
+                    .enumerate()
+                {
+                    subinventory[first_sample + j] = (sample - start) as u16;
+                }
+            } else {
+                inventory.push(SPILL_FLAG | spill.len());
+                let ones_in_block = ones_per_inventory.min(count - (i << log2_ones_per_inventory));
+                let spill_end = spill.len() + ones_in_block;
+                let mut word_index = start / BITS;
+                let mut word = (words[word_index] >> (start % BITS)) << (start % BITS);
+                while spill.len() < spill_end {
+                    if word == 0 {
+                        word_index += 1;
+                        word = words[word_index];
+                        continue;
+                    }
+                    spill.push(word_index * BITS + word.trailing_zeros() as usize);
+                    word &= word - 1;
+                }
+            }
+        }
+
+        Self {
+            bits,
+            inventory,
+            subinventory,
+            spill,
+            log2_ones_per_inventory,
+            log2_ones_per_sub,
+        }
+    }
+}
+
+impl<B: BitLength, I, S> BitLength for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+impl<B: BitCount, I, S> BitCount for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+impl<B: BitCount + AsRef<[usize]>, I: AsRef<[usize]>, S: AsRef<[u16]>> Select
+    for SimpleSelect<B, I, S>
+{
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        let inventory_index = rank >> self.log2_ones_per_inventory;
+        let inventory = *self.inventory.as_ref().get_unchecked(inventory_index);
+        let rank_in_block = rank & ((1 << self.log2_ones_per_inventory) - 1);
+
+        if inventory & SPILL_FLAG != 0 {
+            return *self
+                .spill
+                .as_ref()
+                .get_unchecked((inventory & !SPILL_FLAG) + rank_in_block);
+        }
+
+        let sub_index = (inventory_index
+            << (self.log2_ones_per_inventory - self.log2_ones_per_sub))
+            + (rank_in_block >> self.log2_ones_per_sub);
+        let pos = inventory + *self.subinventory.as_ref().get_unchecked(sub_index) as usize;
+        let mut residual = rank & ((1 << self.log2_ones_per_sub) - 1);
+
+        let words = self.bits.as_ref();
+        let mut word_index = pos / BITS;
+        let bit_index = pos % BITS;
+        let mut word = (words.get_unchecked(word_index) >> bit_index) << bit_index;
+        loop {
+            let bit_count = word.count_ones() as usize;
+            if residual < bit_count {
+                break;
+            }
+            word_index += 1;
+            word = *words.get_unchecked(word_index);
+            residual -= bit_count;
+        }
+
+        word_index * BITS + word.select_in_word(residual)
+    }
+}
+
+/// If the underlying implementation has a hint for select, forward the methods.
+impl<B: SelectHinted + AsRef<[usize]>, I: AsRef<[usize]>, S: AsRef<[u16]>> SelectHinted
+    for SimpleSelect<B, I, S>
+{
+    #[inline(always)]
+    unsafe fn select_unchecked_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
+        self.bits.select_unchecked_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// If the underlying implementation has select zero, forward the methods.
+impl<B: SelectZero, I, S> SelectZero for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// If the underlying implementation has a hint for select zero, forward the methods.
+impl<B: SelectZeroHinted, I, S> SelectZeroHinted for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    unsafe fn select_zero_unchecked_hinted(
+        &self,
+        rank: usize,
+        pos: usize,
+        rank_at_pos: usize,
+    ) -> usize {
+        self.bits
+            .select_zero_unchecked_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// If the underlying implementation has rank, forward the methods.
+impl<B: Rank, I, S> Rank for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn rank(&self, pos: usize) -> usize {
+        self.bits.rank(pos)
+    }
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_unchecked(pos)
+    }
+}
+
+/// If the underlying implementation has rank zero, forward the methods.
+impl<B: RankZero, I, S> RankZero for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn rank_zero(&self, pos: usize) -> usize {
+        self.bits.rank_zero(pos)
+    }
+    #[inline(always)]
+    unsafe fn rank_zero_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_zero_unchecked(pos)
+    }
+}
+
+/// Forget the index.
+impl<B> ConvertTo<B> for SimpleSelect<B, Vec<usize>, Vec<u16>> {
+    #[inline(always)]
+    fn convert_to(self) -> Result<B> {
+        Ok(self.bits)
+    }
+}
+
+/// Create and add a selection structure.
+impl<B: BitLength + BitCount + AsRef<[usize]>> ConvertTo<SimpleSelect<B, Vec<usize>, Vec<u16>>>
+    for B
+{
+    #[inline(always)]
+    fn convert_to(self) -> Result<SimpleSelect<B, Vec<usize>, Vec<u16>>> {
+        Ok(SimpleSelect::new(self))
+    }
+}
+
+impl<B: AsRef<[usize]>, I, S> AsRef<[usize]> for SimpleSelect<B, I, S> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
@@ -0,0 +1,438 @@
+/*
+ *
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Static functions based on fuse graphs.
+
+use crate::mph::spooky::spooky_short;
+use crate::prelude::*;
+use anyhow::{bail, Result};
+use epserde::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// A trait for keys that can be hashed to a 128-bit signature.
+pub trait Remap {
+    fn remap(key: &Self, seed: u64) -> [u64; 2];
+}
+
+impl Remap for String {
+    fn remap(key: &Self, seed: u64) -> [u64; 2] {
+        let spooky = spooky_short(key.as_ref(), seed);
+        [spooky[0], spooky[1]]
+    }
+}
+
+impl Remap for Vec<u8> {
+    fn remap(key: &Self, seed: u64) -> [u64; 2] {
+        let spooky = spooky_short(key, seed);
+        [spooky[0], spooky[1]]
+    }
+}
+
+impl Remap for u64 {
+    fn remap(key: &Self, seed: u64) -> [u64; 2] {
+        let spooky = spooky_short(&key.to_ne_bytes(), seed);
+        [spooky[0], spooky[1]]
+    }
+}
+
+#[derive(Debug, Default)]
+struct EdgeList(usize);
+impl EdgeList {
+    const DEG_SHIFT: usize = usize::BITS as usize - 10;
+    const EDGE_INDEX_MASK: usize = (1_usize << EdgeList::DEG_SHIFT) - 1;
+    const DEG: usize = 1_usize << EdgeList::DEG_SHIFT;
+
+    #[inline(always)]
+    fn add(&mut self, edge: usize) {
+        self.0 += EdgeList::DEG | edge;
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, edge: usize) {
+        self.0 -= EdgeList::DEG | edge;
+    }
+
+    #[inline(always)]
+    fn degree(&self) -> usize {
+        self.0 >> EdgeList::DEG_SHIFT
+    }
+
+    #[inline(always)]
+    fn edge_index(&self) -> usize {
+        self.0 & EdgeList::EDGE_INDEX_MASK
+    }
+
+    #[inline(always)]
+    fn dec(&mut self) {
+        self.0 -= EdgeList::DEG;
+    }
+}
+
+/// A static function based on fuse graphs.
+///
+/// Keys are hashed to a 128-bit signature, which is used to select a chunk
+/// and then an edge of a fuse 3-hypergraph: vertices are divided in
+/// `l` + 2 segments, and the three vertices of an edge lie in three
+/// consecutive segments. The value associated with a key is the xor of the
+/// values associated with the vertices of its edge, which are stored in a
+/// [`CompactArray`] with the requested bit width.
+///
+/// The structure is built by peeling the hypergraph of each chunk; if
+/// peeling fails, the keys are hashed again with a different seed.
+/// Seeds are generated deterministically, so building a function twice
+/// from the same keys and values yields the same structure.
+///
+/// # Examples
+/// ```
+/// use sux::sf::fuse::Function;
+///
+/// let keys = (0..1000_u64).collect::<Vec<_>>();
+/// let values = (0..1000).map(|i| i % 7).collect::<Vec<_>>();
+/// let func = Function::new(&keys, &values, 3).unwrap();
+///
+/// for (key, &value) in keys.iter().zip(values.iter()) {
+///     assert_eq!(func.get(key), value);
+/// }
+/// ```
+///
+/// # Reference:
+/// - [Stefan Walzer, Peeling Close to the Orientability Threshold – Spatial Coupling in Hashing-Based Data Structures](https://doi.org/10.1137/1.9781611976465.143)
+#[derive(Epserde, Debug, Clone)]
+pub struct Function<T, S = CompactArray<Vec<usize>>> {
+    seed: u64,
+    l: usize,
+    num_keys: usize,
+    chunk_mask: u64,
+    segment_size: usize,
+    values: S,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T, S> Function<T, S> {
+    /// Return the number of keys of the function.
+    pub fn len(&self) -> usize {
+        self.num_keys
+    }
+
+    /// Return whether the function has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Remap, S: VSlice> Function<T, S> {
+    /// Return the value associated with a signature.
+    pub fn get_by_sig(&self, sig: &[u64; 2]) -> usize {
+        let edge = edge(sig, self.l, self.segment_size);
+        let chunk = chunk(sig, self.chunk_mask);
+        let chunk_offset = chunk * self.segment_size * (self.l + 2);
+        self.values.get(edge[0] + chunk_offset)
+            ^ self.values.get(edge[1] + chunk_offset)
+            ^ self.values.get(edge[2] + chunk_offset)
+    }
+
+    /// Return the value associated with a key.
+    ///
+    /// If the key was not used to build the function, the result is
+    /// arbitrary.
+    #[inline(always)]
+    pub fn get(&self, key: &T) -> usize {
+        self.get_by_sig(&T::remap(key, self.seed))
+    }
+}
+
+impl<T: Remap> Function<T> {
+    /// Build a function mapping each key to the corresponding value,
+    /// using `bit_width` bits per value.
+    ///
+    /// Return an error if the number of keys and values differ, if some
+    /// value does not fit in `bit_width` bits, or if there are duplicate keys.
+    pub fn new(keys: &[T], values: &[usize], bit_width: usize) -> Result<Self> {
+        if keys.len() != values.len() {
+            bail!(
+                "The number of keys ({}) and values ({}) differ",
+                keys.len(),
+                values.len()
+            );
+        }
+        if bit_width > usize::BITS as usize {
+            bail!("The bit width must be at most {}", usize::BITS);
+        }
+        if let Some(value) = values
+            .iter()
+            .find(|&&v| bit_width < usize::BITS as usize && v >> bit_width != 0)
+        {
+            bail!("Value {} does not fit in {} bits", value, bit_width);
+        }
+
+        let mut seed = SEED;
+        for _ in 0..MAX_ATTEMPTS {
+            let sigs = keys
+                .iter()
+                .zip(values.iter())
+                .map(|(key, &value)| (T::remap(key, seed), value))
+                .collect::<Vec<_>>();
+            if let Some(func) = Self::try_build(sigs, bit_width, seed)? {
+                return Ok(func);
+            }
+            seed = next_seed(seed);
+        }
+        bail!(
+            "Could not build the function after {} attempts",
+            MAX_ATTEMPTS
+        );
+    }
+
+    /// Try to build the function using the given signatures and values,
+    /// returning `None` if the hypergraph of some chunk cannot be peeled.
+    fn try_build(
+        mut sigs: Vec<([u64; 2], usize)>,
+        bit_width: usize,
+        seed: u64,
+    ) -> Result<Option<Self>> {
+        let num_chunks = 1 << chunk_bits(sigs.len());
+        let chunk_mask = num_chunks as u64 - 1;
+        let (counts, cumul) = count_sort(&mut sigs, num_chunks, |x| chunk(&x.0, chunk_mask));
+
+        let (l, segment_size) = fuse_parameters(*counts.iter().max().unwrap());
+        let num_vertices = segment_size * (l + 2);
+
+        let chunks = (0..num_chunks)
+            .map(|chunk| &sigs[cumul[chunk]..cumul[chunk + 1]])
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "rayon")]
+        let solutions = chunks
+            .par_iter()
+            .map(|sigs| Self::solve_chunk(sigs, l, segment_size))
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let solutions = chunks
+            .iter()
+            .map(|sigs| Self::solve_chunk(sigs, l, segment_size))
+            .collect::<Vec<_>>();
+
+        let mut values = CompactArray::new(bit_width, num_vertices * num_chunks);
+        for (chunk, solution) in solutions.into_iter().enumerate() {
+            let Some(chunk_values) = solution else {
+                if has_duplicates(chunks[chunk].iter().map(|x| x.0)) {
+                    bail!("Duplicate keys");
+                }
+                return Ok(None);
+            };
+            let chunk_offset = chunk * num_vertices;
+            for (v, &value) in chunk_values.iter().enumerate() {
+                values.set(chunk_offset + v, value);
+            }
+        }
+
+        Ok(Some(Function {
+            seed,
+            l,
+            num_keys: sigs.len(),
+            chunk_mask,
+            segment_size,
+            values,
+            _marker: core::marker::PhantomData,
+        }))
+    }
+
+    /// Peel the hypergraph of a chunk and assign values to its vertices,
+    /// returning `None` if peeling fails.
+    fn solve_chunk(
+        sigs: &[([u64; 2], usize)],
+        l: usize,
+        segment_size: usize,
+    ) -> Option<Vec<usize>> {
+        let num_vertices = segment_size * (l + 2);
+        let mut stack = peel(sigs.len(), num_vertices, |e| {
+            edge(&sigs[e].0, l, segment_size)
+        })?;
+
+        let mut values = vec![0; num_vertices];
+        while let Some((v, edge_index)) = stack.pop() {
+            values[v] = edge(&sigs[edge_index].0, l, segment_size)
+                .iter()
+                .filter(|&&x| x != v)
+                .fold(sigs[edge_index].1, |acc, &x| acc ^ values[x]);
+        }
+        Some(values)
+    }
+}
+
+/// The seed used to hash keys at the first attempt.
+pub(crate) const SEED: u64 = 0x5EED_C0FF_EE15_600D;
+/// The maximum number of seeds tried before giving up.
+pub(crate) const MAX_ATTEMPTS: usize = 64;
+
+/// Return the seed to try after `seed`.
+#[inline(always)]
+pub(crate) const fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_add(0x9E3779B97F4A7C15)
+}
+
+/// Return the chunk of a signature.
+#[inline(always)]
+#[must_use]
+pub(crate) fn chunk(sig: &[u64; 2], bit_mask: u64) -> usize {
+    (sig[0] & bit_mask) as usize
+}
+
+/// Return the edge of a signature in the fuse graph of its chunk.
+#[inline(always)]
+#[must_use]
+pub(crate) fn edge(sig: &[u64; 2], l: usize, segment_size: usize) -> [usize; 3] {
+    let first_segment = sig[0] as usize >> 16 & (l - 1);
+    [
+        (((sig[0] >> 32) * segment_size as u64) >> 32) as usize + first_segment * segment_size,
+        (((sig[1] & 0xFFFFFFFF) * segment_size as u64) >> 32) as usize
+            + (first_segment + 1) * segment_size,
+        (((sig[1] >> 32) * segment_size as u64) >> 32) as usize
+            + (first_segment + 2) * segment_size,
+    ]
+}
+
+/// Return the number of bits used to select a chunk when there are `n` keys.
+pub(crate) fn chunk_bits(n: usize) -> usize {
+    let eps = 0.001;
+    if n <= 1 << 21 {
+        0
+    } else {
+        let t = (n as f64 * eps * eps / 2.0).ln();
+        if t > 0.0 {
+            ((t - t.ln()) / 2_f64.ln()).ceil() as usize
+        } else {
+            0
+        }
+    }
+}
+
+/// Return whether there are duplicate signatures.
+pub(crate) fn has_duplicates(sigs: impl Iterator<Item = [u64; 2]>) -> bool {
+    let mut sigs = sigs.collect::<Vec<_>>();
+    sigs.sort_unstable();
+    sigs.windows(2).any(|w| w[0] == w[1])
+}
+
+/// Peel a fuse graph with `num_edges` edges and `num_vertices` vertices,
+/// returning the pairs (hinge, edge index) in peeling order, or `None`
+/// if peeling fails.
+pub(crate) fn peel(
+    num_edges: usize,
+    num_vertices: usize,
+    edge: impl Fn(usize) -> [usize; 3],
+) -> Option<Vec<(usize, usize)>> {
+    let mut edge_lists = Vec::new();
+    edge_lists.resize_with(num_vertices, EdgeList::default);
+
+    for edge_index in 0..num_edges {
+        for &v in edge(edge_index).iter() {
+            edge_lists[v].add(edge_index);
+        }
+    }
+
+    let mut stack = Vec::with_capacity(num_edges);
+    let mut visit = Vec::new();
+    for v in 0..num_vertices {
+        if edge_lists[v].degree() != 1 {
+            continue;
+        }
+        visit.push(v);
+        while let Some(v) = visit.pop() {
+            // The degree might have dropped to zero in the meantime
+            if edge_lists[v].degree() != 1 {
+                continue;
+            }
+            let edge_index = edge_lists[v].edge_index();
+            edge_lists[v].dec();
+            stack.push((v, edge_index));
+            for &x in edge(edge_index).iter() {
+                if x != v {
+                    edge_lists[x].remove(edge_index);
+                    if edge_lists[x].degree() == 1 {
+                        visit.push(x);
+                    }
+                }
+            }
+        }
+    }
+
+    if stack.len() != num_edges {
+        return None;
+    }
+    Some(stack)
+}
+
+/// Return the number of segments minus two, which is a power of two, and the
+/// segment size for a fuse graph with `n` edges.
+///
+/// Parameters follow the heuristics for binary fuse filters: small graphs
+/// need more vertices per edge and shorter segments to be peelable with
+/// high probability.
+///
+/// # Reference:
+/// - [Thomas Mueller Graf and Daniel Lemire, Binary Fuse Filters: Fast and Smaller Than Xor Filters](https://arxiv.org/abs/2201.01174)
+pub(crate) fn fuse_parameters(n: usize) -> (usize, usize) {
+    if n < 2 {
+        return (1, 1);
+    }
+    let ln_n = (n as f64).ln();
+    let segment_length = 1 << ((ln_n / 3.33_f64.ln() + 2.25).floor() as usize).min(18);
+    let size_factor = f64::max(1.125, 0.875 + 0.25 * 1E6_f64.ln() / ln_n);
+    let num_vertices = (n as f64 * size_factor).ceil() as usize;
+    // Rounding up keeps segments no longer than the target length
+    let l = (num_vertices / segment_length)
+        .saturating_sub(2)
+        .max(1)
+        .next_power_of_two();
+    (l, (num_vertices + l + 1) / (l + 2))
+}
+
+/// Sort `data` by the given key, which must be smaller than `num_keys`,
+/// returning the number of elements with each key and their cumulative
+/// counts.
+pub(crate) fn count_sort<T: Copy + Clone, F: Fn(&T) -> usize>(
+    data: &mut [T],
+    num_keys: usize,
+    key: F,
+) -> (Vec<usize>, Vec<usize>) {
+    if num_keys == 1 {
+        return (vec![data.len()], vec![0, data.len()]);
+    }
+    let mut counts = vec![0; num_keys];
+
+    for sig in &*data {
+        counts[key(sig)] += 1;
+    }
+
+    let mut cumul = vec![0; counts.len() + 1];
+    for i in 1..cumul.len() {
+        cumul[i] += cumul[i - 1] + counts[i - 1];
+    }
+    let end = data.len() - counts.last().unwrap();
+
+    let mut pos = cumul[1..].to_vec();
+    let mut i = 0;
+    while i < end {
+        let mut sig = data[i];
+
+        loop {
+            let slot = key(&sig);
+            pos[slot] -= 1;
+            if pos[slot] <= i {
+                break;
+            }
+            core::mem::swap(&mut data[pos[slot]], &mut sig);
+        }
+        data[i] = sig;
+        i += counts[key(&sig)];
+    }
+
+    (counts, cumul)
+}
@@ -9,16 +9,25 @@
 
 //! Ported from <https://github.com/vigna/Sux4J/blob/master/c/sf3.c>
 
+use crate::bits::mmap_slice::MmapSlice;
+use crate::mph::hypergraph::{distinct, multiplicity, peel, solve_mod2};
 use crate::mph::spooky::{spooky_short, spooky_short_rehash};
-use anyhow::Result;
+use anyhow::{bail, Result};
+use epserde::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Read;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
 
-/// A stub to load and access Genuzio-Ottaviano-Vigna static functions.
+/// A structure to build, load and access Genuzio-Ottaviano-Vigna static functions.
 ///
-/// To generate the structure you must use the Java version:
+/// The structure can be built natively using [`new`](crate::sf::gov3::GOV3::new),
+/// and stored with the [`dump`](crate::sf::gov3::GOV3::dump) method in the
+/// same format used by the Java version.
+///
+/// Alternatively, you can generate the structure using the Java version:
 /// ```bash
 /// java it.unimi.dsi.sux4j.mph.GOV3Function --byte-array SOURCE test.sf
 /// ```
@@ -30,20 +39,25 @@ use std::path::Path;
 /// echo '((it.unimi.dsi.sux4j.mph.GOV3Function)it.unimi.dsi.fastutil.io.BinIO.loadObject("test.sf")).dump("test.csf");' | jshell
 /// ```
 ///
-/// You can now load the dumped file with the [`load`](crate::sf::gov3::GOV3::load) method.
+/// You can now load the dumped file with the [`load`](crate::sf::gov3::GOV3::load) method,
+/// or map it in memory without copying with the [`load_mmap`](crate::sf::gov3::GOV3::load_mmap) method.
+///
+/// As in the case of [`GOVMPH`](crate::mph::gov::GOVMPH), arrays can be
+/// stored in any type implementing `AsRef<[u64]>`, and the structure can be
+/// serialized with ε-serde.
 ///
 /// # Reference:
 /// - [Marco Genuzio, Giuseppe Ottaviano, and Sebastiano Vigna, Fast Scalable Construction of (Minimal Perfect Hash) Functions](https://arxiv.org/pdf/1603.04330.pdf)
 /// - [Java version with `dump` method](https://github.com/vigna/Sux4J/blob/master/src/it/unimi/dsi/sux4j/mph/GOV3Function.java)
 
-#[derive(Debug)]
-pub struct GOV3 {
+#[derive(Epserde, Debug)]
+pub struct GOV3<D = Vec<u64>> {
     pub size: u64,
     pub width: u64,
     pub multiplier: u64,
     pub global_seed: u64,
-    pub offset_and_seed: Vec<u64>,
-    pub array: Vec<u64>,
+    pub offset_and_seed: D,
+    pub array: D,
 }
 
 impl GOV3 {
@@ -65,17 +79,15 @@ impl GOV3 {
         }
 
         macro_rules! read_array {
-            ($file:expr, $type:ty) => {{
-                // create a bytes buffer big enough for $len elements of type $type
+            ($file:expr) => {{
                 let len = read!($file, u64) as usize;
-                let bytes = len * core::mem::size_of::<$type>();
-                let mut buffer: Vec<u8> = vec![0; bytes];
-                // read the file in the buffer
-                $file.read_exact(&mut buffer)?;
-                // convert the buffer Vec<u8> into a Vec<$type>
-                let ptr = buffer.as_mut_ptr();
-                core::mem::forget(buffer);
-                unsafe { Vec::from_raw_parts(ptr as *mut $type, len, len) }
+                let mut array = vec![0_u64; len];
+                // read the file directly in the (properly aligned) array
+                $file.read_exact(bytemuck::cast_slice_mut(&mut array))?;
+                for word in array.iter_mut() {
+                    *word = u64::from_le(*word);
+                }
+                array
             }};
         }
         // actually lod the data :)
@@ -83,8 +95,8 @@ impl GOV3 {
         let width = read!(file, u64);
         let multiplier = read!(file, u64);
         let global_seed = read!(file, u64);
-        let offset_and_seed = read_array!(file, u64);
-        let array = read_array!(file, u64);
+        let offset_and_seed = read_array!(file);
+        let array = read_array!(file);
 
         Ok(Self {
             size,
@@ -97,26 +109,297 @@ impl GOV3 {
     }
 }
 
+impl GOV3<MmapSlice> {
+    /// Given a path to a file `.csf3` generated from the java version,
+    /// map it in memory and access it without copying.
+    pub fn load_mmap<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let words = MmapSlice::new(path)?;
+        let header = words.as_ref();
+        if header.len() < 4 {
+            bail!("The file is too short to contain a GOV3 structure");
+        }
+        let (offset_and_seed, pos) = words.array(4)?;
+        let (array, _) = words.array(pos)?;
+
+        Ok(Self {
+            size: header[0],
+            width: header[1],
+            multiplier: header[2],
+            global_seed: header[3],
+            offset_and_seed,
+            array,
+        })
+    }
+}
+
+impl<D: AsRef<[u64]>> GOV3<D> {
+    /// Store this structure in a file, in the same format used by the `dump`
+    /// method of the Java version.
+    pub fn dump<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path.as_ref())?);
+        self.dump_writer(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Store this structure in a generic `Write` implementor, in the format
+    /// read by [`load_reader`](crate::sf::gov3::GOV3::load_reader).
+    pub fn dump_writer<F: Write>(&self, mut file: F) -> Result<()> {
+        file.write_all(&self.size.to_le_bytes())?;
+        file.write_all(&self.width.to_le_bytes())?;
+        file.write_all(&self.multiplier.to_le_bytes())?;
+        file.write_all(&self.global_seed.to_le_bytes())?;
+        for array in [self.offset_and_seed.as_ref(), self.array.as_ref()] {
+            file.write_all(&(array.len() as u64).to_le_bytes())?;
+            for word in array.iter() {
+                file.write_all(&word.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl GOV3 {
+    /// The global seed used to hash keys at the first attempt.
+    const GLOBAL_SEED: u64 = 0x5EED_C0FF_EE15_600D;
+    /// The maximum number of global seeds tried before giving up.
+    const MAX_ATTEMPTS: usize = 64;
+
+    /// Build a static function mapping each key to the corresponding value,
+    /// using `width` bits per value.
+    ///
+    /// Keys are divided into buckets of about 1500 elements, and for each
+    /// bucket we look for a seed such that the associated system on GF(2)
+    /// is solvable. The 3-hypergraph of the bucket is peeled, and the
+    /// equations associated with its 2-core, if any, are solved by lazy
+    /// Gaussian elimination. Buckets are solved in parallel if the `rayon`
+    /// feature is enabled.
+    ///
+    /// If some bucket cannot be solved with any seed, keys are hashed
+    /// again using a different global seed.
+    ///
+    /// Return an error if the number of keys and values differ, if some
+    /// value does not fit in `width` bits, or if there are duplicate keys.
+    ///
+    /// # Examples
+    /// ```
+    /// use sux::sf::gov3::GOV3;
+    ///
+    /// let keys = (0..1000).map(|i| format!("key{}", i)).collect::<Vec<_>>();
+    /// let values = (0..1000).map(|i| i * 7 % 1000).collect::<Vec<_>>();
+    /// let func = GOV3::new(&keys, &values, 10).unwrap();
+    ///
+    /// for (key, &value) in keys.iter().zip(values.iter()) {
+    ///     assert_eq!(func.get_byte_array(key.as_bytes()), value);
+    /// }
+    /// ```
+    pub fn new<K: AsRef<[u8]>>(keys: &[K], values: &[u64], width: u64) -> Result<Self> {
+        if keys.len() != values.len() {
+            bail!(
+                "The number of keys ({}) and values ({}) differ",
+                keys.len(),
+                values.len()
+            );
+        }
+        if width == 0 || width > 64 {
+            bail!("The width must be between 1 and 64, but it is {}", width);
+        }
+        if let Some(value) = values.iter().find(|&&v| width < 64 && v >> width != 0) {
+            bail!("Value {} does not fit in {} bits", value, width);
+        }
+
+        let mut global_seed = Self::GLOBAL_SEED;
+        for _ in 0..Self::MAX_ATTEMPTS {
+            let entries = keys
+                .iter()
+                .zip(values.iter())
+                .map(|(key, &value)| (spooky_short(key.as_ref(), global_seed), value))
+                .collect::<Vec<_>>();
+            if let Some(func) = Self::try_build(entries, width, global_seed)? {
+                return Ok(func);
+            }
+            global_seed = global_seed.wrapping_add(0x9E3779B97F4A7C15);
+        }
+        bail!(
+            "Could not build the function after {} attempts",
+            Self::MAX_ATTEMPTS
+        );
+    }
+
+    /// Try to build the function using the given signatures and values,
+    /// returning `None` if some bucket cannot be solved.
+    fn try_build(
+        mut entries: Vec<([u64; 4], u64)>,
+        width: u64,
+        global_seed: u64,
+    ) -> Result<Option<Self>> {
+        let size = entries.len() as u64;
+        let num_buckets = ((size + BUCKET_SIZE - 1) / BUCKET_SIZE).max(1);
+        let multiplier = num_buckets * 2;
+
+        entries.sort_unstable_by_key(|(sig, _)| (signature_to_bucket(sig, multiplier), *sig));
+        if entries.windows(2).any(|w| w[0].0 == w[1].0) {
+            bail!("Duplicate keys");
+        }
+
+        let mut bucket_sizes = vec![0; num_buckets as usize];
+        for (sig, _) in &entries {
+            bucket_sizes[signature_to_bucket(sig, multiplier) as usize] += 1;
+        }
+
+        // Vertex offsets of the buckets
+        let mut offset_and_seed = vec![0; num_buckets as usize + 1];
+        for (b, &bucket_size) in bucket_sizes.iter().enumerate() {
+            offset_and_seed[b + 1] = offset_and_seed[b] + num_variables(bucket_size);
+        }
+
+        let mut buckets = Vec::with_capacity(num_buckets as usize);
+        let mut start = 0;
+        for (b, &bucket_size) in bucket_sizes.iter().enumerate() {
+            buckets.push((
+                &entries[start..start + bucket_size as usize],
+                (offset_and_seed[b + 1] - offset_and_seed[b]) as usize,
+            ));
+            start += bucket_size as usize;
+        }
+
+        #[cfg(feature = "rayon")]
+        let solutions = buckets
+            .par_iter()
+            .map(|&(entries, num_vertices)| solve_bucket(entries, num_vertices))
+            .collect::<Option<Vec<_>>>();
+        #[cfg(not(feature = "rayon"))]
+        let solutions = buckets
+            .iter()
+            .map(|&(entries, num_vertices)| solve_bucket(entries, num_vertices))
+            .collect::<Option<Vec<_>>>();
+        let Some(solutions) = solutions else {
+            return Ok(None);
+        };
+
+        let num_vertices = offset_and_seed[num_buckets as usize];
+        let mut array = vec![0_u64; ((num_vertices * width + 63) / 64) as usize];
+        for (b, (seed, values)) in solutions.into_iter().enumerate() {
+            for (v, &value) in values.iter().enumerate() {
+                set_value(&mut array, offset_and_seed[b] + v as u64, width, value);
+            }
+            offset_and_seed[b] |= seed << (64 - SEED_BITS);
+        }
+
+        Ok(Some(Self {
+            size,
+            width,
+            multiplier,
+            global_seed,
+            offset_and_seed,
+            array,
+        }))
+    }
+}
+
+/// Return the number of variables used for a bucket with `num_keys` keys.
+///
+/// Besides 9% of additional variables, we add a small constant to make
+/// small buckets solvable with high probability.
+#[inline(always)]
+#[must_use]
+const fn num_variables(num_keys: u64) -> u64 {
+    (num_keys * 109 + 99) / 100 + 10
+}
+
+/// Return the vertices of an edge that appear an odd number of times, as
+/// the others cancel out in the associated equation.
+#[inline(always)]
+fn odd_vertices(edge: &[usize; 3]) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    distinct(edge).filter(|&v| multiplicity(edge, v) % 2 == 1)
+}
+
+/// Find a seed for a bucket and the corresponding values of its vertices.
+///
+/// The xor of the values of the vertices of an edge must be equal to the
+/// value associated with the key of the edge.
+fn solve_bucket(entries: &[([u64; 4], u64)], num_vertices: usize) -> Option<(u64, Vec<u64>)> {
+    (0..1 << SEED_BITS).find_map(|seed: u64| {
+        let edges = entries
+            .iter()
+            .map(|(sig, _)| {
+                signature_to_equation(sig, seed << (64 - SEED_BITS), num_vertices as u64)
+                    .map(|v| v as usize)
+            })
+            .collect::<Vec<_>>();
+
+        let peeling = peel(num_vertices, edges.len(), |e| odd_vertices(&edges[e]));
+
+        // The variables of the system are the vertices of the 2-core
+        let mut var = vec![usize::MAX; num_vertices];
+        let mut core_vertices = vec![];
+        for &e in &peeling.core {
+            for v in odd_vertices(&edges[e]) {
+                if var[v] == usize::MAX {
+                    var[v] = core_vertices.len();
+                    core_vertices.push(v);
+                }
+            }
+        }
+        let equations = peeling
+            .core
+            .iter()
+            .map(|&e| {
+                (
+                    odd_vertices(&edges[e]).map(|v| var[v]).collect::<Vec<_>>(),
+                    entries[e].1,
+                )
+            })
+            .collect::<Vec<_>>();
+        let solution = solve_mod2(core_vertices.len(), &equations)?;
+
+        let mut values = vec![0_u64; num_vertices];
+        for (&v, &x) in core_vertices.iter().zip(solution.iter()) {
+            values[v] = x;
+        }
+
+        // Peeled edges are assigned in reverse peeling order
+        for &(e, h) in peeling.order.iter().rev() {
+            values[h] = odd_vertices(&edges[e])
+                .filter(|&v| v != h)
+                .fold(entries[e].1, |x, v| x ^ values[v]);
+        }
+
+        Some((seed, values))
+    })
+}
+
+impl<D: AsRef<[u64]>> GOV3<D> {
     pub fn size(&self) -> u64 {
         self.size
     }
 
     pub fn get_byte_array(&self, key: &[u8]) -> u64 {
         let signature = spooky_short(key, self.global_seed);
-        let bucket = ((((signature[0] as u128) >> 1) * (self.multiplier as u128)) >> 64) as u64;
-        let offset_seed = self.offset_and_seed[bucket as usize];
+        let bucket = signature_to_bucket(&signature, self.multiplier);
+        let offset_and_seed = self.offset_and_seed.as_ref();
+        let array = self.array.as_ref();
+        let offset_seed = offset_and_seed[bucket as usize];
         let bucket_offset = offset_seed & OFFSET_MASK;
-        let num_variables =
-            (self.offset_and_seed[bucket as usize + 1] & OFFSET_MASK) - bucket_offset;
+        let num_variables = (offset_and_seed[bucket as usize + 1] & OFFSET_MASK) - bucket_offset;
         let e = signature_to_equation(&signature, offset_seed & (!OFFSET_MASK), num_variables);
-        get_value(&self.array, e[0] + bucket_offset, self.width)
-            ^ get_value(&self.array, e[1] + bucket_offset, self.width)
-            ^ get_value(&self.array, e[2] + bucket_offset, self.width)
+        get_value(array, e[0] + bucket_offset, self.width)
+            ^ get_value(array, e[1] + bucket_offset, self.width)
+            ^ get_value(array, e[2] + bucket_offset, self.width)
     }
 }
 
 const OFFSET_MASK: u64 = u64::MAX >> 8;
+/// The expected number of keys in a bucket.
+const BUCKET_SIZE: u64 = 1500;
+/// The number of bits used to store the seed of a bucket.
+const SEED_BITS: u32 = 8;
+
+#[inline(always)]
+#[must_use]
+fn signature_to_bucket(signature: &[u64; 4], multiplier: u64) -> u64 {
+    ((((signature[0] as u128) >> 1) * (multiplier as u128)) >> 64) as u64
+}
 
 #[inline(always)]
 #[must_use]
@@ -145,3 +428,14 @@ fn get_value(array: &[u64], mut pos: u64, width: u64) -> u64 {
             | ((array[start_word as usize + 1] << (64 + l - start_bit)) >> l)
     }
 }
+
+#[inline(always)]
+fn set_value(array: &mut [u64], mut pos: u64, width: u64, value: u64) {
+    pos *= width;
+    let start_word = (pos / 64) as usize;
+    let start_bit = pos % 64;
+    array[start_word] |= value << start_bit;
+    if start_bit + width > 64 {
+        array[start_word + 1] |= value >> (64 - start_bit);
+    }
+}
@@ -21,4 +21,5 @@
 //! blocks of space-efficient data structures such as (compressed) full-text indexes, (monotone)
 //! MPHFs, Bloom filter-like data structures, and prefix-search data structures.
 
+pub mod fuse;
 pub mod gov3;
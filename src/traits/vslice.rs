@@ -33,8 +33,26 @@ We provide implementations for `Vec<usize>`, `Vec<AtomicUsize>`, `&[usize]`,
 and `&[AtomicUsize]` that view their elements as values with a bit width
 equal to that of `usize`. The implementations based on atomic types implements
 [`VSliceAtomic`].
+Value slices can be scanned using [`VSlice::iter_values`], [`VSlice::iter_from`]
+and [`VSlice::range`], which return iterators of a type specific to each
+implementation; for example, the iterators of
+[`CompactArray`](crate::bits::compact_array::CompactArray)
+decode values sequentially from a buffer of bits instead of locating each value
+independently. The method iterating over all values is not named `iter`, as it
+would shadow [`slice::iter`] on vectors whenever [`VSlice`] is in scope.
+Implementations that do not need a specific iterator can use [`VSliceIter`].
+
+A window of a value slice can be passed around as a value slice using
+[`VSlice::slice`] and [`VSliceMut::slice_mut`], which return a
+[`VSliceView`] and a [`VSliceViewMut`], respectively. Mutable views of
+implementations of [`VSliceWords`], which store each value in its own word,
+can be split into disjoint mutable slices.
 */
+use core::iter::{Copied, Take};
+use core::ops::Range;
 use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 const BITS: usize = core::mem::size_of::<usize>() * 8;
 
@@ -69,6 +87,14 @@ macro_rules! panic_if_value {
 }
 pub(crate) use panic_if_value;
 
+macro_rules! panic_if_range_out_of_bounds {
+    ($range: expr, $len: expr) => {
+        if $range.start > $range.end || $range.end > $len {
+            panic!("Range out of bounds: {:?} with length {}", $range, $len)
+        }
+    };
+}
+
 macro_rules! debug_assert_bounds {
     ($index: expr, $len: expr) => {
         debug_assert!(
@@ -82,6 +108,11 @@ macro_rules! debug_assert_bounds {
 
 /// A value slice.
 pub trait VSlice: VSliceCore {
+    /// The type of the iterators over the values of the slice.
+    type Iter<'a>: Iterator<Item = usize> + ExactSizeIterator
+    where
+        Self: 'a;
+
     /// Return the value at the specified index.
     ///
     /// # Safety
@@ -96,6 +127,109 @@ pub trait VSlice: VSliceCore {
         panic_if_out_of_bounds!(index, self.len());
         unsafe { self.get_unchecked(index) }
     }
+
+    /// Return an iterator over the values of the slice.
+    ///
+    /// This is equivalent to [`iter_from(0)`](VSlice::iter_from).
+    fn iter_values(&self) -> Self::Iter<'_> {
+        self.iter_from(0)
+    }
+
+    /// Return an iterator over the values of the slice starting
+    /// at the specified index.
+    ///
+    /// # Panics
+    /// If `from` is greater than [len](`VSliceCore::len`).
+    fn iter_from(&self, from: usize) -> Self::Iter<'_>;
+
+    /// Return an iterator over the values of the slice in the specified range.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    fn range(&self, range: Range<usize>) -> Take<Self::Iter<'_>> {
+        panic_if_range_out_of_bounds!(range, self.len());
+        self.iter_from(range.start).take(range.len())
+    }
+
+    /// Return a [view](VSliceView) of the values of the slice in the specified range.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    fn slice(&self, range: Range<usize>) -> VSliceView<'_, Self> {
+        panic_if_range_out_of_bounds!(range, self.len());
+        VSliceView {
+            slice: self,
+            offset: range.start,
+            len: range.len(),
+        }
+    }
+
+    /// Copy all values of the slice into `dst`.
+    ///
+    /// # Panics
+    /// If the length of `dst` is not equal to [len](`VSliceCore::len`).
+    fn copy_to(&self, dst: &mut [usize]) {
+        assert_eq!(
+            dst.len(),
+            self.len(),
+            "Destination length {} is not equal to slice length {}",
+            dst.len(),
+            self.len()
+        );
+        for (d, value) in dst.iter_mut().zip(self.iter_from(0)) {
+            *d = value;
+        }
+    }
+}
+
+/// An iterator over the values of a [`VSlice`] that simply calls
+/// [`VSlice::get_unchecked`] on each index.
+#[derive(Debug, Clone)]
+pub struct VSliceIter<'a, S: ?Sized> {
+    slice: &'a S,
+    index: usize,
+    len: usize,
+}
+
+impl<'a, S: VSlice + ?Sized> VSliceIter<'a, S> {
+    /// Create an iterator over the values of `slice` starting at `from`.
+    ///
+    /// # Panics
+    /// If `from` is greater than the length of `slice`.
+    pub fn new(slice: &'a S, from: usize) -> Self {
+        let len = slice.len();
+        assert!(from <= len, "Index out of bounds: {} > {}", from, len);
+        Self {
+            slice,
+            index: from,
+            len,
+        }
+    }
+}
+
+impl<'a, S: VSlice + ?Sized> Iterator for VSliceIter<'a, S> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.index == self.len {
+            return None;
+        }
+        self.index += 1;
+        Some(unsafe { self.slice.get_unchecked(self.index - 1) })
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len - self.index, Some(self.len - self.index))
+    }
+}
+
+impl<'a, S: VSlice + ?Sized> ExactSizeIterator for VSliceIter<'a, S> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len - self.index
+    }
 }
 
 /// A mutable value slice.
@@ -124,6 +258,31 @@ pub trait VSliceMut: VSlice {
             self.set_unchecked(index, value);
         }
     }
+
+    /// Return a [mutable view](VSliceViewMut) of the values of the slice
+    /// in the specified range.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    fn slice_mut(&mut self, range: Range<usize>) -> VSliceViewMut<'_, Self> {
+        panic_if_range_out_of_bounds!(range, self.len());
+        VSliceViewMut {
+            slice: self,
+            offset: range.start,
+            len: range.len(),
+        }
+    }
+}
+
+/// A mutable value slice storing each value in a separate word.
+///
+/// Disjoint ranges of values of such slices do not share memory, so they
+/// can be modified independently: [mutable views](VSliceViewMut) of
+/// implementations of this trait can be split and divided into chunks,
+/// possibly in parallel.
+pub trait VSliceWords: VSliceMut {
+    /// Return the values of the slice as a mutable slice of words.
+    fn as_words_mut(&mut self) -> &mut [usize];
 }
 
 /// A thread-safe value slice supporting atomic operations.
@@ -183,11 +342,24 @@ impl<'a> VSliceCore for &'a [usize] {
 }
 
 impl<'a> VSlice for &'a [usize] {
+    type Iter<'b>
+        = Copied<core::slice::Iter<'b, usize>>
+    where
+        Self: 'b;
+
     #[inline(always)]
     unsafe fn get_unchecked(&self, index: usize) -> usize {
         debug_assert_bounds!(index, self.len());
         *<[usize]>::get_unchecked(self, index)
     }
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        self[from..].iter().copied()
+    }
+    #[inline(always)]
+    fn copy_to(&self, dst: &mut [usize]) {
+        dst.copy_from_slice(self);
+    }
 }
 
 impl<'a> VSliceCore for &'a [AtomicUsize] {
@@ -226,11 +398,24 @@ impl<'a> VSliceCore for &'a mut [usize] {
 }
 
 impl<'a> VSlice for &'a mut [usize] {
+    type Iter<'b>
+        = Copied<core::slice::Iter<'b, usize>>
+    where
+        Self: 'b;
+
     #[inline(always)]
     unsafe fn get_unchecked(&self, index: usize) -> usize {
         debug_assert!(index < self.len(), "{} {}", index, self.len());
         *<[usize]>::get_unchecked(self, index)
     }
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        self[from..].iter().copied()
+    }
+    #[inline(always)]
+    fn copy_to(&self, dst: &mut [usize]) {
+        dst.copy_from_slice(self);
+    }
 }
 
 impl<'a> VSliceMut for &'a mut [usize] {
@@ -241,6 +426,13 @@ impl<'a> VSliceMut for &'a mut [usize] {
     }
 }
 
+impl VSliceWords for &mut [usize] {
+    #[inline(always)]
+    fn as_words_mut(&mut self) -> &mut [usize] {
+        self
+    }
+}
+
 impl<'a> VSliceCore for &'a mut [AtomicUsize] {
     #[inline(always)]
     fn bit_width(&self) -> usize {
@@ -277,11 +469,24 @@ impl VSliceCore for Vec<usize> {
 }
 
 impl VSlice for Vec<usize> {
+    type Iter<'b>
+        = Copied<core::slice::Iter<'b, usize>>
+    where
+        Self: 'b;
+
     #[inline(always)]
     unsafe fn get_unchecked(&self, index: usize) -> usize {
         debug_assert_bounds!(index, self.len());
         *<[usize]>::get_unchecked(self, index)
     }
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        self[from..].iter().copied()
+    }
+    #[inline(always)]
+    fn copy_to(&self, dst: &mut [usize]) {
+        dst.copy_from_slice(self);
+    }
 }
 
 impl VSliceMut for Vec<usize> {
@@ -292,6 +497,13 @@ impl VSliceMut for Vec<usize> {
     }
 }
 
+impl VSliceWords for Vec<usize> {
+    #[inline(always)]
+    fn as_words_mut(&mut self) -> &mut [usize] {
+        self
+    }
+}
+
 impl VSliceCore for Vec<AtomicUsize> {
     #[inline(always)]
     fn bit_width(&self) -> usize {
@@ -314,3 +526,254 @@ impl VSliceAtomic for Vec<AtomicUsize> {
         <[AtomicUsize]>::get_unchecked(self, index).store(value, order);
     }
 }
+
+/// A view of a range of a [`VSlice`], returned by [`VSlice::slice`].
+///
+/// Views are value slices themselves, and they can be further
+/// [sliced](VSliceView::slice), [split](VSliceView::split_at), and
+/// [divided into chunks](VSliceView::chunks). Slicing a view returns a
+/// view of the underlying slice, so nesting has no cost. If the feature
+/// "rayon" is enabled, chunks can be [processed in
+/// parallel](VSliceView::par_chunks).
+#[derive(Debug)]
+pub struct VSliceView<'a, S: ?Sized> {
+    slice: &'a S,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a, S: ?Sized> Clone for VSliceView<'a, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S: ?Sized> Copy for VSliceView<'a, S> {}
+
+impl<'a, S: VSlice + ?Sized> VSliceView<'a, S> {
+    /// Return a view of the specified range of this view.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    pub fn slice(&self, range: Range<usize>) -> VSliceView<'a, S> {
+        panic_if_range_out_of_bounds!(range, self.len);
+        VSliceView {
+            slice: self.slice,
+            offset: self.offset + range.start,
+            len: range.len(),
+        }
+    }
+
+    /// Divide this view into two views at an index.
+    ///
+    /// The first view contains the values in [0..`mid`), and the
+    /// second the values in [`mid`..[len](`VSliceCore::len`)).
+    ///
+    /// # Panics
+    /// If `mid` is greater than [len](`VSliceCore::len`).
+    pub fn split_at(&self, mid: usize) -> (VSliceView<'a, S>, VSliceView<'a, S>) {
+        (self.slice(0..mid), self.slice(mid..self.len))
+    }
+
+    /// Return an iterator over consecutive views of `chunk_size` values,
+    /// except for the last one, which might be shorter.
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    pub fn chunks(&self, chunk_size: usize) -> impl Iterator<Item = VSliceView<'a, S>> {
+        assert!(chunk_size != 0, "Chunk size must be nonzero");
+        let view = *self;
+        (0..self.len)
+            .step_by(chunk_size)
+            .map(move |start| view.slice(start..(start + chunk_size).min(view.len)))
+    }
+
+    /// Return a parallel iterator over consecutive views of `chunk_size` values,
+    /// except for the last one, which might be shorter.
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    #[cfg(feature = "rayon")]
+    pub fn par_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl IndexedParallelIterator<Item = VSliceView<'a, S>>
+    where
+        S: Sync,
+    {
+        assert!(chunk_size != 0, "Chunk size must be nonzero");
+        let view = *self;
+        (0..(self.len + chunk_size - 1) / chunk_size)
+            .into_par_iter()
+            .map(move |i| view.slice(i * chunk_size..((i + 1) * chunk_size).min(view.len)))
+    }
+}
+
+impl<'a, S: VSliceCore + ?Sized> VSliceCore for VSliceView<'a, S> {
+    #[inline(always)]
+    fn bit_width(&self) -> usize {
+        self.slice.bit_width()
+    }
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, S: VSlice + ?Sized> VSlice for VSliceView<'a, S> {
+    type Iter<'b>
+        = Take<S::Iter<'a>>
+    where
+        Self: 'b;
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        debug_assert_bounds!(index, self.len);
+        self.slice.get_unchecked(self.offset + index)
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        assert!(
+            from <= self.len,
+            "Index out of bounds: {} > {}",
+            from,
+            self.len
+        );
+        self.slice
+            .iter_from(self.offset + from)
+            .take(self.len - from)
+    }
+}
+
+/// A mutable view of a range of a [`VSliceMut`], returned by
+/// [`VSliceMut::slice_mut`].
+///
+/// Mutable views can be further [sliced](VSliceViewMut::slice_mut), but,
+/// contrarily to [views](VSliceView), in general they cannot be split: in
+/// implementations such as [`CompactArray`](crate::bits::compact_array::CompactArray)
+/// values in disjoint ranges might share a word, so writing to them
+/// concurrently would be a data race. Mutable views of implementations of
+/// [`VSliceWords`] can be [split](VSliceViewMut::split_at_mut) and
+/// [divided into chunks](VSliceViewMut::chunks_mut) (in parallel, too, if the
+/// feature "rayon" is enabled), obtaining mutable slices of words, which are
+/// mutable value slices themselves.
+#[derive(Debug)]
+pub struct VSliceViewMut<'a, S: ?Sized> {
+    slice: &'a mut S,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a, S: VSliceMut + ?Sized> VSliceViewMut<'a, S> {
+    /// Return a view of the specified range of this view.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    pub fn slice(&self, range: Range<usize>) -> VSliceView<'_, S> {
+        panic_if_range_out_of_bounds!(range, self.len);
+        VSliceView {
+            slice: self.slice,
+            offset: self.offset + range.start,
+            len: range.len(),
+        }
+    }
+
+    /// Return a mutable view of the specified range of this view.
+    ///
+    /// # Panics
+    /// If `range` is not contained in [0..[len](`VSliceCore::len`)).
+    pub fn slice_mut(&mut self, range: Range<usize>) -> VSliceViewMut<'_, S> {
+        panic_if_range_out_of_bounds!(range, self.len);
+        VSliceViewMut {
+            slice: self.slice,
+            offset: self.offset + range.start,
+            len: range.len(),
+        }
+    }
+}
+
+impl<'a, S: VSliceWords + ?Sized> VSliceViewMut<'a, S> {
+    /// Divide this view into two mutable slices at an index.
+    ///
+    /// The first slice contains the values in [0..`mid`), and the
+    /// second the values in [`mid`..[len](`VSliceCore::len`)).
+    ///
+    /// # Panics
+    /// If `mid` is greater than [len](`VSliceCore::len`).
+    pub fn split_at_mut(&mut self, mid: usize) -> (&mut [usize], &mut [usize]) {
+        self.as_words_mut().split_at_mut(mid)
+    }
+
+    /// Return an iterator over consecutive mutable slices of `chunk_size`
+    /// values, except for the last one, which might be shorter.
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    pub fn chunks_mut(&mut self, chunk_size: usize) -> core::slice::ChunksMut<'_, usize> {
+        self.as_words_mut().chunks_mut(chunk_size)
+    }
+
+    /// Return a parallel iterator over consecutive mutable slices of
+    /// `chunk_size` values, except for the last one, which might be shorter.
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    #[cfg(feature = "rayon")]
+    pub fn par_chunks_mut(&mut self, chunk_size: usize) -> rayon::slice::ChunksMut<'_, usize> {
+        self.as_words_mut().par_chunks_mut(chunk_size)
+    }
+}
+
+impl<'a, S: VSliceCore + ?Sized> VSliceCore for VSliceViewMut<'a, S> {
+    #[inline(always)]
+    fn bit_width(&self) -> usize {
+        self.slice.bit_width()
+    }
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, S: VSlice + ?Sized> VSlice for VSliceViewMut<'a, S> {
+    type Iter<'b>
+        = Take<S::Iter<'b>>
+    where
+        Self: 'b;
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        debug_assert_bounds!(index, self.len);
+        self.slice.get_unchecked(self.offset + index)
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Iter<'_> {
+        assert!(
+            from <= self.len,
+            "Index out of bounds: {} > {}",
+            from,
+            self.len
+        );
+        self.slice
+            .iter_from(self.offset + from)
+            .take(self.len - from)
+    }
+}
+
+impl<'a, S: VSliceMut + ?Sized> VSliceMut for VSliceViewMut<'a, S> {
+    #[inline(always)]
+    unsafe fn set_unchecked(&mut self, index: usize, value: usize) {
+        debug_assert_bounds!(index, self.len);
+        self.slice.set_unchecked(self.offset + index, value);
+    }
+}
+
+impl<'a, S: VSliceWords + ?Sized> VSliceWords for VSliceViewMut<'a, S> {
+    #[inline(always)]
+    fn as_words_mut(&mut self) -> &mut [usize] {
+        &mut self.slice.as_words_mut()[self.offset..self.offset + self.len]
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Ordinal trees using the depth-first unary degree sequence.
+
+use crate::prelude::*;
+use crate::tree::push_degrees;
+use anyhow::Result;
+use epserde::*;
+
+/// An ordinal tree represented by its depth-first unary degree sequence
+/// (DFUDS).
+///
+/// Nodes are numbered from zero in depth-first preorder, the root being node
+/// zero. The representation is a sequence of 2*n* [balanced
+/// parentheses](BalancedParens) containing an open parenthesis followed, for
+/// each node in preorder, by as many open parentheses as its children,
+/// followed by a closed parenthesis. The description of node *i* follows
+/// the (*i* − 1)-th closed parenthesis, and the closed parenthesis preceding
+/// the description of a node matches the open parenthesis representing
+/// the node in the description of its parent. Navigation reduces to
+/// [ranking](RankZero) and [selection](SelectZero) of zeros, and to the
+/// primitives of [`BalancedParens`]. Contrarily to [`Louds`], the size of
+/// a subtree is computed in a single search.
+///
+/// The default backend adds [`Rank9`] and [`QuantumZeroIndex`] to a
+/// [`CountBitVec`].
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// //     0
+/// //    / \
+/// //   1   4
+/// //  / \   \
+/// // 2   3   5
+/// let tree = Dfuds::from_degrees([2, 2, 0, 0, 1, 0]).unwrap();
+///
+/// assert_eq!(tree.parent(3), Some(1));
+/// assert_eq!(tree.first_child(4), Some(5));
+/// assert_eq!(tree.next_sibling(1), Some(4));
+/// assert_eq!(tree.child(1, 1), Some(3));
+/// assert_eq!(tree.depth(5), 2);
+/// assert_eq!(tree.subtree_size(1), 3);
+/// ```
+///
+/// # Reference:
+/// - [David Benoit, Erik D. Demaine, J. Ian Munro, Rajeev Raman, Venkatesh Raman, and S. Srinivasa Rao, Representing Trees of Higher Degree](https://doi.org/10.1007/s00453-004-1146-6)
+#[derive(Epserde, Debug, Clone)]
+pub struct Dfuds<B = Rank9<QuantumZeroIndex<CountBitVec>>, E = Vec<i64>> {
+    parens: BalancedParens<B, E>,
+}
+
+impl<B, E> Dfuds<B, E> {
+    /// # Safety
+    /// `parens` must be the depth-first unary degree sequence of a tree.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(parens: BalancedParens<B, E>) -> Self {
+        Self { parens }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> BalancedParens<B, E> {
+        self.parens
+    }
+}
+
+impl Dfuds {
+    /// Build a tree given the degrees of its nodes in depth-first preorder.
+    ///
+    /// Return an error if the degrees do not describe a tree.
+    pub fn from_degrees(degrees: impl IntoIterator<Item = usize>) -> Result<Self> {
+        let mut bits = BitVec::new(0);
+        bits.push(true);
+        push_degrees(&mut bits, degrees)?;
+
+        let bits: CountBitVec = bits.into();
+        let bits: QuantumZeroIndex<CountBitVec> = bits.convert_to()?;
+        Ok(Self {
+            parens: BalancedParens::new(bits.convert_to()?),
+        })
+    }
+}
+
+impl<B: BitLength + RankZero + SelectZero + AsRef<[usize]>, E: AsRef<[i64]>> Dfuds<B, E> {
+    /// Return the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.parens.len() / 2
+    }
+
+    #[inline(always)]
+    fn check(&self, node: usize) {
+        assert!(
+            node < self.num_nodes(),
+            "Node out of bounds: {} >= {}",
+            node,
+            self.num_nodes()
+        );
+    }
+
+    /// Return the position of the description of a node.
+    #[inline(always)]
+    fn start(&self, node: usize) -> usize {
+        if node == 0 {
+            1
+        } else {
+            unsafe { self.parens.select_zero_unchecked(node - 1) + 1 }
+        }
+    }
+
+    /// Return the node whose description follows the closed parenthesis
+    /// matching the open parenthesis at `pos`.
+    #[inline(always)]
+    fn node_of_open(&self, pos: usize) -> usize {
+        let close = self.parens.find_close(pos).unwrap();
+        unsafe { self.parens.rank_zero_unchecked(close + 1) }
+    }
+
+    /// Return the number of children of a node.
+    pub fn degree(&self, node: usize) -> usize {
+        self.check(node);
+        unsafe { self.parens.select_zero_unchecked(node) - self.start(node) }
+    }
+
+    /// Return the child of given index of a node, or `None` if the node
+    /// does not have so many children.
+    pub fn child(&self, node: usize, index: usize) -> Option<usize> {
+        if index >= self.degree(node) {
+            return None;
+        }
+        // Children are represented in reverse order
+        let pos = unsafe { self.parens.select_zero_unchecked(node) } - 1 - index;
+        Some(self.node_of_open(pos))
+    }
+
+    /// Return the first child of a node, or `None` if the node is a leaf.
+    pub fn first_child(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        // In preorder, the first child follows its parent
+        (self.degree(node) > 0).then_some(node + 1)
+    }
+
+    /// Return the open parenthesis representing a node in the
+    /// description of its parent.
+    #[inline(always)]
+    fn open_of(&self, node: usize) -> usize {
+        self.parens.find_open(self.start(node) - 1).unwrap()
+    }
+
+    /// Return the next sibling of a node, or `None` if the node is the
+    /// last child of its parent.
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        let parent = self.parent(node)?;
+        let pos = self.open_of(node);
+        (pos > self.start(parent)).then(|| self.node_of_open(pos - 1))
+    }
+
+    /// Return the parent of a node, or `None` for the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        if node == 0 {
+            return None;
+        }
+        Some(unsafe { self.parens.rank_zero_unchecked(self.open_of(node)) })
+    }
+
+    /// Return the depth of a node, the root having depth zero.
+    pub fn depth(&self, mut node: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.parent(node) {
+            node = parent;
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Return the number of nodes of the subtree rooted at a node,
+    /// including the node itself.
+    pub fn subtree_size(&self, node: usize) -> usize {
+        self.check(node);
+        // The descriptions of the subtree end when the excess drops
+        // below that at the start of the description of the node
+        let start = self.start(node);
+        let end = self
+            .parens
+            .fwd_search(start, self.parens.excess(start) - 1)
+            .unwrap();
+        unsafe { self.parens.rank_zero_unchecked(end) - node }
+    }
+}
@@ -0,0 +1,189 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Ordinal trees using the level-order unary degree sequence.
+
+use crate::prelude::*;
+use crate::tree::push_degrees;
+use anyhow::Result;
+use epserde::*;
+
+/// An ordinal tree represented by its level-order unary degree sequence
+/// (LOUDS).
+///
+/// Nodes are numbered from zero in breadth-first order, the root being node
+/// zero. The representation is a bit vector of 2*n* + 1 bits containing
+/// `10` followed, for each node in breadth-first order, by as many ones as
+/// its children, followed by a zero. The *i*-th one represents node *i*,
+/// and the description of node *i* follows the *i*-th zero, so navigation
+/// reduces to [ranking](Rank) and [selection](Select) over the bit vector.
+///
+/// The default backend adds [`Rank9`], [`QuantumIndex`], and
+/// [`QuantumZeroIndex`] to a [`CountBitVec`].
+///
+/// # Examples
+/// ```
+/// use sux::prelude::*;
+///
+/// //     0
+/// //    / \
+/// //   1   2
+/// //  / \   \
+/// // 3   4   5
+/// let tree = Louds::from_degrees([2, 2, 1, 0, 0, 0]).unwrap();
+///
+/// assert_eq!(tree.parent(4), Some(1));
+/// assert_eq!(tree.first_child(2), Some(5));
+/// assert_eq!(tree.next_sibling(3), Some(4));
+/// assert_eq!(tree.child(1, 1), Some(4));
+/// assert_eq!(tree.depth(5), 2);
+/// assert_eq!(tree.subtree_size(1), 3);
+/// ```
+///
+/// # Reference:
+/// - [Guy Jacobson, Space-efficient static trees and graphs](https://doi.org/10.1109/SFCS.1989.63533)
+#[derive(Epserde, Debug, Clone)]
+pub struct Louds<B = Rank9<QuantumZeroIndex<QuantumIndex<CountBitVec>>>> {
+    bits: B,
+}
+
+impl<B> Louds<B> {
+    /// # Safety
+    /// `bits` must be the level-order unary degree sequence of a tree.
+    #[inline(always)]
+    pub unsafe fn from_raw_parts(bits: B) -> Self {
+        Self { bits }
+    }
+
+    #[inline(always)]
+    pub fn into_raw_parts(self) -> B {
+        self.bits
+    }
+}
+
+impl Louds {
+    /// Build a tree given the degrees of its nodes in breadth-first order.
+    ///
+    /// Return an error if the degrees do not describe a tree.
+    pub fn from_degrees(degrees: impl IntoIterator<Item = usize>) -> Result<Self> {
+        let mut bits = BitVec::new(0);
+        bits.push(true);
+        bits.push(false);
+        push_degrees(&mut bits, degrees)?;
+
+        let bits: CountBitVec = bits.into();
+        let bits: QuantumIndex<CountBitVec> = bits.convert_to()?;
+        let bits: QuantumZeroIndex<QuantumIndex<CountBitVec>> = bits.convert_to()?;
+        Ok(Self {
+            bits: bits.convert_to()?,
+        })
+    }
+}
+
+impl<B: Rank + RankZero + Select + SelectZero + AsRef<[usize]>> Louds<B> {
+    /// Return the number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.bits.count()
+    }
+
+    #[inline(always)]
+    fn check(&self, node: usize) {
+        assert!(
+            node < self.num_nodes(),
+            "Node out of bounds: {} >= {}",
+            node,
+            self.num_nodes()
+        );
+    }
+
+    #[inline(always)]
+    fn bit(&self, pos: usize) -> bool {
+        self.bits.as_ref()[pos / usize::BITS as usize] >> (pos % usize::BITS as usize) & 1 != 0
+    }
+
+    /// Return the number of children of a node.
+    pub fn degree(&self, node: usize) -> usize {
+        self.check(node);
+        unsafe {
+            self.bits.select_zero_unchecked(node + 1) - self.bits.select_zero_unchecked(node) - 1
+        }
+    }
+
+    /// Return the child of given index of a node, or `None` if the node
+    /// does not have so many children.
+    pub fn child(&self, node: usize, index: usize) -> Option<usize> {
+        if index >= self.degree(node) {
+            return None;
+        }
+        unsafe {
+            Some(
+                self.bits
+                    .rank_unchecked(self.bits.select_zero_unchecked(node) + 1 + index),
+            )
+        }
+    }
+
+    /// Return the first child of a node, or `None` if the node is a leaf.
+    pub fn first_child(&self, node: usize) -> Option<usize> {
+        self.child(node, 0)
+    }
+
+    /// Return the next sibling of a node, or `None` if the node is the
+    /// last child of its parent.
+    pub fn next_sibling(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        // The ones of siblings are consecutive
+        let pos = unsafe { self.bits.select_unchecked(node) };
+        self.bit(pos + 1).then_some(node + 1)
+    }
+
+    /// Return the parent of a node, or `None` for the root.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.check(node);
+        if node == 0 {
+            return None;
+        }
+        // The one of the node is in the description of the parent
+        unsafe {
+            Some(
+                self.bits
+                    .rank_zero_unchecked(self.bits.select_unchecked(node))
+                    - 1,
+            )
+        }
+    }
+
+    /// Return the depth of a node, the root having depth zero.
+    pub fn depth(&self, mut node: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.parent(node) {
+            node = parent;
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Return the number of nodes of the subtree rooted at a node,
+    /// including the node itself.
+    pub fn subtree_size(&self, node: usize) -> usize {
+        self.check(node);
+        // The descendants at each level form an interval of nodes
+        let (mut start, mut end) = (node, node + 1);
+        let mut size = 0;
+        while start < end {
+            size += end - start;
+            unsafe {
+                start = self
+                    .bits
+                    .rank_unchecked(self.bits.select_zero_unchecked(start));
+                end = self
+                    .bits
+                    .rank_unchecked(self.bits.select_zero_unchecked(end));
+            }
+        }
+        size
+    }
+}
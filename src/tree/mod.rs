@@ -0,0 +1,46 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Succinct ordinal trees.
+
+pub mod dfuds;
+pub mod louds;
+
+pub mod prelude {
+    pub use super::dfuds::*;
+    pub use super::louds::*;
+}
+
+use crate::bits::bit_vec::BitVec;
+use anyhow::{bail, Result};
+
+/// Append to `bits`, for each degree `d`, `d` ones followed by a zero,
+/// checking that the degrees describe a tree.
+///
+/// The check is the same for degrees in breadth-first and depth-first
+/// order: each node but the root must be a child of a previous node.
+pub(crate) fn push_degrees(
+    bits: &mut BitVec,
+    degrees: impl IntoIterator<Item = usize>,
+) -> Result<()> {
+    // The number of nodes that are children of nodes already seen,
+    // plus the root, minus the nodes already seen
+    let mut pending = 1_usize;
+    for (node, degree) in degrees.into_iter().enumerate() {
+        if pending == 0 {
+            bail!("Node {} is not a descendant of the root", node);
+        }
+        pending = pending - 1 + degree;
+        for _ in 0..degree {
+            bits.push(true);
+        }
+        bits.push(false);
+    }
+    if pending != 0 {
+        bail!("The degrees are missing {} nodes", pending);
+    }
+    Ok(())
+}
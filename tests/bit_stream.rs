@@ -0,0 +1,134 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use sux::bits::bit_stream::{BitReader, BitWriter};
+use sux::bits::bit_vec::BitVec;
+
+#[derive(Debug, Clone, Copy)]
+enum Code {
+    Bits(usize),
+    Unary,
+    Gamma,
+    Delta,
+    Zeta(usize),
+    Golomb(u64),
+}
+
+#[test]
+fn test_bit_stream() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let codes = [
+        Code::Bits(0),
+        Code::Bits(1),
+        Code::Bits(13),
+        Code::Bits(64),
+        Code::Unary,
+        Code::Gamma,
+        Code::Delta,
+        Code::Zeta(1),
+        Code::Zeta(3),
+        Code::Zeta(64),
+        Code::Golomb(1),
+        Code::Golomb(7),
+        Code::Golomb(16),
+    ];
+
+    let mut writer = BitWriter::default();
+    let mut written = Vec::new();
+    for _ in 0..10_000 {
+        let code = codes[rng.gen_range(0..codes.len())];
+        let value = match code {
+            Code::Bits(0) => 0,
+            Code::Bits(width) => rng.gen::<u64>() >> (64 - width),
+            Code::Unary | Code::Golomb(_) => rng.gen_range(0..300),
+            // Mostly small values, sometimes very large ones
+            _ => rng.gen::<u64>() >> rng.gen_range(1..64),
+        };
+        let start = writer.len();
+        let len = match code {
+            Code::Bits(width) => writer.write_bits(value, width),
+            Code::Unary => writer.write_unary(value),
+            Code::Gamma => writer.write_gamma(value),
+            Code::Delta => writer.write_delta(value),
+            Code::Zeta(k) => writer.write_zeta(value, k),
+            Code::Golomb(b) => writer.write_golomb(value, b),
+        };
+        assert_eq!(writer.len(), start + len);
+        written.push((code, value, start));
+    }
+    let bits = writer.into_bit_vec();
+
+    let mut reader = BitReader::new(bits.as_ref());
+    for &(code, value, start) in &written {
+        assert_eq!(reader.position(), start);
+        let read = match code {
+            Code::Bits(width) => reader.read_bits(width),
+            Code::Unary => reader.read_unary(),
+            Code::Gamma => reader.read_gamma(),
+            Code::Delta => reader.read_delta(),
+            Code::Zeta(k) => reader.read_zeta(k),
+            Code::Golomb(b) => reader.read_golomb(b),
+        };
+        assert_eq!(read, value, "{:?}", code);
+    }
+    assert_eq!(reader.position(), bits.len());
+
+    // Random access using the recorded positions
+    for &(code, value, start) in written.iter().rev().step_by(7) {
+        reader.set_position(start);
+        if let Code::Gamma = code {
+            assert_eq!(reader.read_gamma(), value);
+        }
+    }
+}
+
+#[test]
+fn test_bit_stream_zeta_wide() {
+    // For large k, minimal binary codes can be wider than 64 bits
+    for k in 2..=63 {
+        let mut values = vec![u64::MAX - 1, u64::MAX - 2];
+        for h in 1..=63 / k {
+            let left = 1_u64 << (h * k);
+            values.extend([left - 3, left - 2, left - 1, left, left + 1]);
+        }
+        let mut writer = BitWriter::default();
+        for &value in &values {
+            writer.write_zeta(value, k);
+        }
+        let bits = writer.into_bit_vec();
+        let mut reader = BitReader::new(bits.as_ref());
+        for &value in &values {
+            assert_eq!(reader.read_zeta(k), value, "k = {}", k);
+        }
+        assert_eq!(reader.position(), bits.len());
+    }
+}
+
+#[test]
+fn test_bit_stream_append() {
+    let mut bits = BitVec::new(0);
+    bits.extend([true, false, true]);
+    let mut writer = BitWriter::new(bits);
+    assert_eq!(writer.len(), 3);
+    assert_eq!(writer.write_gamma(0), 1);
+    assert_eq!(writer.write_gamma(1), 3);
+    assert_eq!(writer.write_delta(1), 4);
+    assert_eq!(writer.write_unary(100), 101);
+    let bits = writer.into_bit_vec();
+    assert_eq!(bits.len(), 112);
+    // Bits past the end are zero
+    assert_eq!(bits.count_ones(), 2 + 1 + 1 + 1 + 1);
+
+    let mut reader = BitReader::new(&bits);
+    assert_eq!(reader.read_bits(3), 0b101);
+    assert_eq!(reader.read_gamma(), 0);
+    assert_eq!(reader.read_gamma(), 1);
+    assert_eq!(reader.read_delta(), 1);
+    assert_eq!(reader.read_unary(), 100);
+}
@@ -10,7 +10,7 @@ use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::{RngCore, SeedableRng};
-use sux::bits::bit_vec::BitVec;
+use sux::bits::bit_vec::{BitVec, CountBitVec};
 
 #[test]
 fn test_bit_vec() {
@@ -98,6 +98,210 @@ fn test_bit_vec() {
     }
 }
 
+#[test]
+fn test_bulk_ops() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000] {
+        let mut a = BitVec::new(len);
+        let mut b = BitVec::new(len);
+        for i in 0..len {
+            a.set(i, rng.next_u64() % 2 != 0);
+            b.set(i, rng.next_u64() % 2 != 0);
+        }
+
+        let and = a.and(&b);
+        let or = a.or(&b);
+        let xor = a.xor(&b);
+        let and_not = a.and_not(&b);
+        let not = a.not();
+        for i in 0..len {
+            assert_eq!(and.get(i), a.get(i) & b.get(i));
+            assert_eq!(or.get(i), a.get(i) | b.get(i));
+            assert_eq!(xor.get(i), a.get(i) ^ b.get(i));
+            assert_eq!(and_not.get(i), a.get(i) & !b.get(i));
+            assert_eq!(not.get(i), !a.get(i));
+        }
+        // Bits past the end stay zero
+        assert_eq!(not.count_ones(), len - a.count_ones());
+
+        let mut c = a.clone();
+        c.xor_in_place(&b);
+        c.xor_in_place(&b);
+        c.not_in_place();
+        c.not_in_place();
+        for i in 0..len {
+            assert_eq!(c.get(i), a.get(i));
+        }
+
+        #[cfg(feature = "rayon")]
+        {
+            for (x, y) in [
+                (a.par_and(&b), and),
+                (a.par_or(&b), or),
+                (a.par_xor(&b), xor),
+                (a.par_and_not(&b), and_not),
+                (a.par_not(), not),
+            ] {
+                assert_eq!(x.as_ref(), y.as_ref());
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_bulk_ops_different_lengths() {
+    BitVec::new(10).and(&BitVec::new(11));
+}
+
+#[test]
+fn test_growable() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut b = BitVec::with_capacity(10);
+    let mut model = Vec::new();
+
+    for _ in 0..1000 {
+        match rng.next_u64() % 8 {
+            0 => {
+                assert_eq!(b.pop(), model.pop());
+            }
+            1 => {
+                let new_len = (rng.next_u64() % 300) as usize;
+                let value = rng.next_u64() % 2 != 0;
+                b.resize(new_len, value);
+                model.resize(new_len, value);
+            }
+            2 => {
+                let other = (0..rng.next_u64() % 150)
+                    .map(|_| rng.next_u64() % 2 != 0)
+                    .collect::<Vec<_>>();
+                let mut c = BitVec::new(0);
+                c.extend(other.iter().copied());
+                b.append(&c);
+                model.extend(other);
+            }
+            _ => {
+                let value = rng.next_u64() % 2 != 0;
+                b.push(value);
+                model.push(value);
+            }
+        }
+
+        assert_eq!(b.len(), model.len());
+        for (i, &value) in model.iter().enumerate() {
+            assert_eq!(b.get(i), value);
+        }
+        // Bits past the end are zero
+        assert_eq!(b.count_ones(), model.iter().filter(|&&x| x).count());
+    }
+}
+
+#[test]
+fn test_iterators() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 200, 1000] {
+        for density in [0, 1, 2, 8] {
+            let mut b = BitVec::new(len);
+            for i in 0..len {
+                // Density 0 means all ones; otherwise, probability 1/density
+                b.set(i, density == 0 || rng.next_u64() % density == 0);
+            }
+            let ones = (0..len).filter(|&i| b.get(i)).collect::<Vec<_>>();
+            let zeros = (0..len).filter(|&i| !b.get(i)).collect::<Vec<_>>();
+
+            for pos in [0, 1, 63, 64, 65, len / 2, len, len + 1] {
+                assert_eq!(
+                    b.iter_ones_from(pos).collect::<Vec<_>>(),
+                    ones.iter()
+                        .copied()
+                        .filter(|&i| i >= pos)
+                        .collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    b.iter_zeros_from(pos).collect::<Vec<_>>(),
+                    zeros
+                        .iter()
+                        .copied()
+                        .filter(|&i| i >= pos)
+                        .collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    b.iter_ones_rev_from(pos).collect::<Vec<_>>(),
+                    ones.iter()
+                        .copied()
+                        .rev()
+                        .filter(|&i| i < pos)
+                        .collect::<Vec<_>>()
+                );
+
+                let mut runs = Vec::<core::ops::Range<usize>>::new();
+                for i in ones.iter().copied().filter(|&i| i >= pos) {
+                    match runs.last_mut() {
+                        Some(run) if run.end == i => run.end += 1,
+                        _ => runs.push(i..i + 1),
+                    }
+                }
+                assert_eq!(b.runs_from(pos).collect::<Vec<_>>(), runs);
+            }
+
+            assert_eq!(b.iter_ones().collect::<Vec<_>>(), ones);
+            assert_eq!(b.iter_zeros().collect::<Vec<_>>(), zeros);
+            assert_eq!(
+                b.iter_ones_rev().collect::<Vec<_>>(),
+                ones.iter().copied().rev().collect::<Vec<_>>()
+            );
+            assert_eq!(b.runs().map(|r| r.len()).sum::<usize>(), ones.len());
+
+            let c: CountBitVec = b.into();
+            assert_eq!(c.iter_ones().collect::<Vec<_>>(), ones);
+            assert_eq!(c.iter_zeros().collect::<Vec<_>>(), zeros);
+        }
+    }
+}
+
+#[test]
+fn test_atomic_fetch_ops() {
+    let b = BitVec::new_atomic(200);
+    assert!(!b.fetch_set(10, Ordering::Relaxed));
+    assert!(b.fetch_set(10, Ordering::Relaxed));
+    assert!(b.fetch_clear(10, Ordering::Relaxed));
+    assert!(!b.fetch_clear(10, Ordering::Relaxed));
+    assert!(!b.fetch_toggle(199, Ordering::Relaxed));
+    assert!(b.get(199, Ordering::Relaxed));
+    assert!(b.fetch_toggle(199, Ordering::Relaxed));
+    assert!(!b.get(199, Ordering::Relaxed));
+
+    b.set(64, true, Ordering::Relaxed);
+    assert_eq!(b.fetch_or_word(1, 0b110, Ordering::Relaxed), 1);
+    assert_eq!(b.fetch_or_word(1, 0, Ordering::Relaxed), 0b111);
+    // The last word contains bits [192..200)
+    assert_eq!(b.fetch_or_word(3, 0xFF, Ordering::Relaxed), 0);
+    assert_eq!(b.count_ones(), 11);
+
+    // Each bit is claimed by exactly one thread
+    let b = BitVec::new_atomic(10000);
+    let claimed = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for i in 0..b.len() {
+                    if !b.fetch_set(i, Ordering::Relaxed) {
+                        claimed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(claimed.load(Ordering::Relaxed), 10000);
+    assert_eq!(b.count_ones(), 10000);
+}
+
+#[test]
+#[should_panic]
+fn test_fetch_or_word_past_end() {
+    BitVec::new_atomic(200).fetch_or_word(3, 0x100, Ordering::Relaxed);
+}
+
 #[test]
 fn test_epserde() {
     let mut rng = SmallRng::seed_from_u64(0);
@@ -0,0 +1,118 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// Return a random balanced sequence of `n` pairs of parentheses.
+fn random_balanced(rng: &mut SmallRng, n: usize) -> Vec<bool> {
+    let mut parens = Vec::with_capacity(2 * n);
+    let (mut open, mut excess) = (0, 0);
+    while parens.len() < 2 * n {
+        // Deep and shallow regions
+        let p_open = if (parens.len() / 3000) % 2 == 0 { 0.6 } else { 0.4 };
+        if open < n && (excess == 0 || rng.gen_bool(p_open)) {
+            parens.push(true);
+            open += 1;
+            excess += 1;
+        } else {
+            parens.push(false);
+            excess -= 1;
+        }
+    }
+    parens
+}
+
+fn build(parens: &[bool]) -> BalancedParens {
+    let mut bits = BitVec::new(0);
+    bits.extend(parens.iter().copied());
+    BalancedParens::new(bits)
+}
+
+#[test]
+fn test_bp() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 256, 257, 1000, 10000] {
+        let parens = random_balanced(&mut rng, n);
+        let bp = build(&parens);
+        assert_eq!(bp.len(), 2 * n);
+
+        let mut excess = vec![0_i64];
+        for &p in &parens {
+            excess.push(excess.last().unwrap() + if p { 1 } else { -1 });
+        }
+        for (pos, &e) in excess.iter().enumerate() {
+            assert_eq!(bp.excess(pos), e);
+        }
+
+        let mut matching = vec![0; 2 * n];
+        let mut enclosing = vec![None; 2 * n];
+        let mut stack = Vec::new();
+        for (pos, &p) in parens.iter().enumerate() {
+            if p {
+                enclosing[pos] = stack.last().copied();
+                stack.push(pos);
+            } else {
+                let open = stack.pop().unwrap();
+                matching[open] = pos;
+                matching[pos] = open;
+            }
+        }
+
+        for (pos, &p) in parens.iter().enumerate() {
+            if p {
+                assert_eq!(bp.find_close(pos), Some(matching[pos]));
+                assert_eq!(bp.enclose(pos), enclosing[pos]);
+            } else {
+                assert_eq!(bp.find_open(pos), Some(matching[pos]));
+            }
+        }
+
+        for _ in 0..1000 {
+            let start = rng.gen_range(0..=2 * n);
+            let end = rng.gen_range(start..=(start + 3000).min(2 * n));
+            let min = *excess[start..=end].iter().min().unwrap();
+            let expected = start + excess[start..=end].iter().position(|&e| e == min).unwrap();
+            assert_eq!(bp.rmq(start, end), expected);
+        }
+        assert_eq!(bp.rmq(0, 2 * n), 0);
+    }
+}
+
+#[test]
+fn test_bp_unbalanced() {
+    // )(()
+    let bp = build(&[false, true, true, false]);
+    assert_eq!(bp.excess(1), -1);
+    assert_eq!(bp.find_open(0), None);
+    assert_eq!(bp.find_close(1), None);
+    assert_eq!(bp.find_close(2), Some(3));
+    assert_eq!(bp.enclose(1), None);
+    assert_eq!(bp.enclose(2), Some(1));
+    assert_eq!(bp.rmq(0, 4), 1);
+}
+
+#[test]
+fn test_bp_epserde() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let parens = random_balanced(&mut rng, 1000);
+    let bp = build(&parens);
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_bp.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file).unwrap());
+    bp.serialize(&mut file).unwrap();
+    drop(file);
+
+    let c = <BalancedParens>::mmap(&tmp_file, epserde::des::Flags::empty()).unwrap();
+    for (pos, &p) in parens.iter().enumerate() {
+        if p {
+            assert_eq!(c.find_close(pos), bp.find_close(pos));
+        }
+    }
+}
@@ -83,3 +83,202 @@ fn test_compact_array_usize() {
     assert_eq!(c.get(2), 0);
     assert_eq!(c.get(3), -1_isize as usize);
 }
+
+#[test]
+fn test_compact_array_push() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 7, 13, 63, 64] {
+        let u = if bit_width == 64 {
+            usize::MAX
+        } else {
+            (1 << bit_width) - 1
+        };
+        let values = (0..1000).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+
+        let mut cp = CompactArray::with_capacity(bit_width, 10);
+        assert!(cp.is_empty());
+        for &value in &values[..500] {
+            cp.push(value);
+        }
+        cp.extend(values[500..].iter().copied());
+        assert_eq!(cp.len(), values.len());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(cp.get(i), value);
+        }
+
+        // Growable arrays are equal to arrays filled by set
+        let mut expected = CompactArray::new(bit_width, values.len());
+        for (i, &value) in values.iter().enumerate() {
+            expected.set(i, value);
+        }
+        assert_eq!(cp, expected);
+
+        for len in [1000, 2000, 777, 64, 1, 0] {
+            cp.truncate(len);
+            let len = len.min(values.len());
+            assert_eq!(cp.len(), len);
+            let mut expected = CompactArray::new(bit_width, len);
+            for (i, &value) in values[..len].iter().enumerate() {
+                expected.set(i, value);
+            }
+            assert_eq!(cp, expected);
+        }
+
+        cp.extend(values.iter().copied());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(cp.get(i), value);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_compact_array_push_too_large() {
+    let mut cp = CompactArray::with_capacity(3, 10);
+    cp.push(8);
+}
+
+#[test]
+fn test_compact_array_repack() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = (0..1000).map(|_| rng.gen_range(0..100)).collect::<Vec<_>>();
+    let mut cp = CompactArray::new(7, 0);
+    cp.extend(values.iter().copied());
+
+    for bit_width in [7, 8, 13, 64, 20, 7] {
+        cp.repack(bit_width).unwrap();
+        assert_eq!(cp.bit_width(), bit_width);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(cp.get(i), value);
+        }
+        let mut expected = CompactArray::new(bit_width, values.len());
+        for (i, &value) in values.iter().enumerate() {
+            expected.set(i, value);
+        }
+        assert_eq!(cp, expected);
+    }
+
+    // Values do not fit: the array is left unchanged
+    assert!(cp.repack(6).is_err());
+    assert!(cp.repack(65).is_err());
+    assert_eq!(cp.bit_width(), 7);
+
+    // Make room for a larger value
+    cp.widen(10);
+    cp.push(1000);
+    assert_eq!(cp.get(1000), 1000);
+    assert_eq!(cp.get(999), values[999]);
+
+    let mut cp = CompactArray::new(5, 10);
+    cp.repack(0).unwrap();
+    assert_eq!(cp, CompactArray::new(0, 10));
+    cp.widen(3);
+    assert_eq!(cp, CompactArray::new(3, 10));
+}
+
+#[test]
+#[should_panic]
+fn test_compact_array_narrow_widen() {
+    let mut cp = CompactArray::new(5, 10);
+    cp.widen(4);
+}
+
+#[test]
+fn test_compact_array_from_iter() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for max in [0, 1, 2, 100, 1 << 40, usize::MAX] {
+        for len in [0, 1, 10, 1000] {
+            let values = (0..len)
+                .map(|_| rng.gen_range(0..=max))
+                .chain(core::iter::once(max))
+                .collect::<Vec<_>>();
+            let bit_width = (usize::BITS - max.leading_zeros()) as usize;
+            let mut expected = CompactArray::new(bit_width, values.len());
+            for (i, &value) in values.iter().enumerate() {
+                expected.set(i, value);
+            }
+
+            let cp = CompactArray::from_slice(&values);
+            assert_eq!(cp.bit_width(), bit_width);
+            assert_eq!(cp, expected);
+
+            let cp: CompactArray = values.iter().copied().collect();
+            assert_eq!(cp, expected);
+
+            let cp: CompactArray<Vec<AtomicUsize>> = values.iter().copied().collect();
+            assert_eq!(cp.bit_width(), bit_width);
+            for (i, &value) in values.iter().enumerate() {
+                assert_eq!(cp.get(i, Ordering::Relaxed), value);
+            }
+
+            #[cfg(feature = "rayon")]
+            {
+                use rayon::prelude::*;
+                let cp: CompactArray = values.par_iter().copied().collect();
+                assert_eq!(cp, expected);
+
+                let cp: CompactArray<Vec<AtomicUsize>> = values.par_iter().copied().collect();
+                let cp: CompactArray = cp.into();
+                assert_eq!(cp, expected);
+            }
+        }
+    }
+
+    let cp = CompactArray::from_slice(&[]);
+    assert_eq!(cp, CompactArray::new(0, 0));
+}
+
+#[test]
+fn test_compact_array_iter() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 8, 13, 31, 32, 63, 64] {
+        let u = if bit_width == 64 {
+            usize::MAX
+        } else {
+            (1 << bit_width) - 1
+        };
+        for len in [0, 1, 10, 1000] {
+            let values = (0..len).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+            let mut cp = CompactArray::new(bit_width, len);
+            for (i, &value) in values.iter().enumerate() {
+                cp.set(i, value);
+            }
+
+            assert_eq!(cp.iter().len(), len);
+            assert_eq!(cp.iter().collect::<Vec<_>>(), values);
+            assert_eq!(cp.iter_values().collect::<Vec<_>>(), values);
+            assert_eq!(values.iter_values().collect::<Vec<_>>(), values);
+            for from in [0, 1, len / 3, len] {
+                let from = from.min(len);
+                assert_eq!(cp.iter_from(from).collect::<Vec<_>>(), values[from..]);
+                assert_eq!(values.iter_from(from).collect::<Vec<_>>(), values[from..]);
+            }
+            for _ in 0..10 {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                assert_eq!(cp.range(start..end).collect::<Vec<_>>(), values[start..end]);
+            }
+
+            let mut dst = vec![0; len];
+            cp.copy_to(&mut dst);
+            assert_eq!(dst, values);
+            let mut dst = vec![0; len];
+            values.as_slice().copy_to(&mut dst);
+            assert_eq!(dst, values);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_compact_array_range_out_of_bounds() {
+    let cp = CompactArray::new(5, 10);
+    let _ = cp.range(5..11);
+}
+
+#[test]
+#[should_panic]
+fn test_compact_array_copy_to_wrong_len() {
+    let cp = CompactArray::new(5, 10);
+    cp.copy_to(&mut [0; 9]);
+}
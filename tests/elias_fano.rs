@@ -104,6 +104,88 @@ fn test_elias_fano() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_elias_fano_simple_select() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(100, 1000), (100, 100), (1000, 100), (1000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+
+        values.sort();
+
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        let ef = efb.build();
+        // Add the selection structure
+        let ef: EliasFano<SimpleSelect<CountBitVec<Vec<usize>>>, CompactArray<Vec<usize>>> =
+            ef.convert_to().unwrap();
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), *v);
+        }
+        // Add also an index on the zeros
+        let ef: EliasFano<
+            QuantumZeroIndex<SimpleSelect<CountBitVec<Vec<usize>>>>,
+            CompactArray<Vec<usize>>,
+        > = ef.convert_to().unwrap();
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), *v);
+            assert_eq!(
+                ef.successor(v),
+                Some((values.partition_point(|x| x < v), *v))
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_elias_fano_simple_select_sparse() -> Result<()> {
+    // A dense region followed by a sparse one, so that some blocks
+    // of the selection structure on the high bits are spilled
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..200_000)
+        .map(|_| rng.gen_range(0..200_000))
+        .collect::<Vec<_>>();
+    values.sort();
+    let mut value = 200_000;
+    for _ in 0..8000 {
+        value += rng.gen_range(500..1500);
+        values.push(value);
+    }
+    let (n, u) = (values.len(), value + 1);
+
+    let build = || -> Result<_> {
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        Ok(efb.build())
+    };
+    let plain = build()?;
+    let ef: EliasFano<SimpleSelect<CountBitVec<Vec<usize>>>, CompactArray<Vec<usize>>> =
+        build()?.convert_to()?;
+    let ef: EliasFano<
+        QuantumZeroIndex<SimpleSelect<CountBitVec<Vec<usize>>>>,
+        CompactArray<Vec<usize>>,
+    > = ef.convert_to()?;
+
+    for i in 0..n {
+        assert_eq!(ef.get(i), plain.get(i));
+    }
+    let queries = values
+        .iter()
+        .step_by(31)
+        .flat_map(|&v| [v.saturating_sub(1), v, v + 1])
+        .chain((0..1000).map(|_| rng.gen_range(0..u + 10)));
+    for query in queries {
+        assert_eq!(ef.successor(&query), plain.successor(&query));
+        assert_eq!(ef.predecessor(&query), plain.predecessor(&query));
+    }
+    Ok(())
+}
+
 #[test]
 fn test_epsserde() -> Result<()> {
     let mut rng = SmallRng::seed_from_u64(0);
@@ -143,3 +225,51 @@ fn test_epsserde() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_succ_pred() -> Result<()> {
+    use std::collections::BTreeSet;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(100, 1000), (100, 100), (1000, 100), (1, 10), (10, 1 << 20)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+
+        values.sort();
+        let set = values.iter().copied().collect::<BTreeSet<_>>();
+
+        let build = || -> Result<DefaultEliasFano> {
+            let mut efb = EliasFanoBuilder::new(n, u);
+            for value in values.iter() {
+                efb.push(*value)?;
+            }
+            Ok(efb.build())
+        };
+        let ef = build()?;
+        // Add the indices, so that select zero is fast
+        let efo: EliasFano<
+            QuantumIndex<CountBitVec<Vec<usize>>, Vec<usize>, 8>,
+            CompactArray<Vec<usize>>,
+        > = build()?.convert_to().unwrap();
+        let efz: EliasFano<
+            QuantumZeroIndex<QuantumIndex<CountBitVec<Vec<usize>>, Vec<usize>, 8>, Vec<usize>, 8>,
+            CompactArray<Vec<usize>>,
+        > = efo.convert_to().unwrap();
+
+        let queries = (0..(u + 2).min(10_000))
+            .chain((0..1000).map(|_| rng.gen_range(0..u + 10)))
+            .collect::<Vec<_>>();
+
+        for v in queries {
+            let first = values.partition_point(|&x| x < v);
+
+            let expected = set.range(v..).next().map(|&x| (first, x));
+            assert_eq!(ef.successor(&v), expected, "successor of {}", v);
+            assert_eq!(efz.successor(&v), expected, "successor of {}", v);
+
+            let expected = set.range(..v).next_back().map(|&x| (first - 1, x));
+            assert_eq!(ef.predecessor(&v), expected, "predecessor of {}", v);
+            assert_eq!(efz.predecessor(&v), expected, "predecessor of {}", v);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use sux::sf::fuse::Function;
+
+#[test]
+fn test_fuse() -> Result<()> {
+    for bit_width in [0, 1, 10, 64] {
+        for n in [0, 1, 2, 10, 100, 1000, 100_000] {
+            let keys = (0..n as u64).collect::<Vec<_>>();
+            let values = (0..n)
+                .map(|i: usize| {
+                    if bit_width == 0 {
+                        0
+                    } else {
+                        i.wrapping_mul(0x9E3779B97F4A7C15) >> (64 - bit_width)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let func = Function::new(&keys, &values, bit_width)?;
+            assert_eq!(func.len(), n);
+            for (key, &value) in keys.iter().zip(values.iter()) {
+                assert_eq!(func.get(key), value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fuse_strings() -> Result<()> {
+    let keys = BufReader::new(File::open("tests/data/mph.txt")?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let values = (0..keys.len()).collect::<Vec<_>>();
+    let func = Function::new(&keys, &values, 14)?;
+    for (key, &value) in keys.iter().zip(values.iter()) {
+        assert_eq!(func.get(key), value);
+    }
+
+    // Construction is deterministic
+    let again = Function::new(&keys, &values, 14)?;
+    for key in &keys {
+        assert_eq!(func.get(key), again.get(key));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fuse_errors() {
+    assert!(Function::new(&[0_u64, 1, 0], &[0, 1, 2], 2).is_err());
+    assert!(Function::new(&[0_u64, 1], &[0, 4], 2).is_err());
+    assert!(Function::new(&[0_u64, 1], &[0], 2).is_err());
+}
+
+#[test]
+fn test_fuse_epserde() -> Result<()> {
+    let keys = (0..1000_u64).collect::<Vec<_>>();
+    let values = (0..1000).collect::<Vec<_>>();
+    let func = Function::new(&keys, &values, 10)?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_fuse.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    func.serialize(&mut file)?;
+    drop(file);
+
+    let c = <Function<u64>>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    for (key, &value) in keys.iter().zip(values.iter()) {
+        assert_eq!(c.get(key), value);
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use sux::mph::fuse_mph::FuseMPH;
+
+#[test]
+fn test_fuse_mph() -> Result<()> {
+    for n in [0, 1, 2, 3, 10, 100, 1000, 100_000] {
+        let keys = (0..n as u64).collect::<Vec<_>>();
+        let mph = FuseMPH::new(&keys)?;
+        assert_eq!(mph.len(), n);
+        mph.verify(&keys)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fuse_mph_strings() -> Result<()> {
+    let keys = BufReader::new(File::open("tests/data/mph.txt")?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mph = FuseMPH::new(&keys)?;
+    mph.verify(&keys)?;
+
+    // Construction is deterministic
+    let again = FuseMPH::new(&keys)?;
+    for key in &keys {
+        assert_eq!(mph.get(key), again.get(key));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fuse_mph_errors() -> Result<()> {
+    assert!(FuseMPH::new(&[0_u64, 1, 0]).is_err());
+
+    let keys = (0..100_u64).collect::<Vec<_>>();
+    let mph = FuseMPH::new(&keys)?;
+    assert!(mph.verify(&keys[..99]).is_err());
+    // Mapping two copies of a key cannot be injective
+    let mut keys = keys;
+    keys[99] = 0;
+    assert!(mph.verify(&keys).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_fuse_mph_epserde() -> Result<()> {
+    let keys = (0..1000_u64).collect::<Vec<_>>();
+    let mph = FuseMPH::new(&keys)?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_fuse_mph.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    mph.serialize(&mut file)?;
+    drop(file);
+
+    let c = <FuseMPH<u64>>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    for key in &keys {
+        assert_eq!(c.get(key), mph.get(key));
+    }
+    Ok(())
+}
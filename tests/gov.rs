@@ -6,6 +6,7 @@
  */
 
 use anyhow::Result;
+use epserde::prelude::*;
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::BufRead;
@@ -37,3 +38,187 @@ fn test_gov3_sf() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_gov_mph_mmap() -> Result<()> {
+    let m = sux::mph::gov::GOVMPH::load("tests/data/test.cmph")?;
+    let c = sux::mph::gov::GOVMPH::load_mmap("tests/data/test.cmph")?;
+    assert_eq!(c.size(), m.size());
+    for line in BufReader::new(File::open("tests/data/mph.txt")?).lines() {
+        let line = line?;
+        assert_eq!(
+            c.get_byte_array(line.as_bytes()),
+            m.get_byte_array(line.as_bytes())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gov3_sf_mmap() -> Result<()> {
+    let c = sux::sf::gov3::GOV3::load_mmap("tests/data/test.csf")?;
+    for (idx, line) in BufReader::new(File::open("tests/data/mph.txt")?)
+        .lines()
+        .enumerate()
+    {
+        let line = line?;
+        assert_eq!(c.get_byte_array(line.as_bytes()), idx as _);
+    }
+
+    // Truncated files are rejected
+    let tmp_file = std::env::temp_dir().join("test_gov3_sf_mmap.csf");
+    let bytes = std::fs::read("tests/data/test.csf")?;
+    std::fs::write(&tmp_file, &bytes[..bytes.len() - 8])?;
+    assert!(sux::sf::gov3::GOV3::load_mmap(&tmp_file).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_gov_mph_build() -> Result<()> {
+    let keys = BufReader::new(File::open("tests/data/mph.txt")?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let m = sux::mph::gov::GOVMPHBuilder::new(0x648a8bd3282bc45b).build(&keys)?;
+    assert_eq!(m.size(), keys.len() as u64);
+
+    // The structure is the one previously generated by this builder with
+    // the same global seed
+    let mut dump = Vec::new();
+    m.dump_writer(&mut dump)?;
+    assert_eq!(dump, std::fs::read("tests/data/test_build.cmph")?);
+    let b = sux::mph::gov::GOVMPH::load("tests/data/test_build.cmph")?;
+    assert_eq!(m.edge_offset_and_seed, b.edge_offset_and_seed);
+    assert_eq!(m.array, b.array);
+
+    // The Java version, which orients the 2-core differently, uses
+    // the same global seed and the same bucket offsets
+    let j = sux::mph::gov::GOVMPH::load("tests/data/test.cmph")?;
+    assert_eq!(m.size, j.size);
+    assert_eq!(m.multiplier, j.multiplier);
+    assert_eq!(m.global_seed, j.global_seed);
+    assert_eq!(m.array.len(), j.array.len());
+    let offset_mask = (1 << 56) - 1;
+    assert_eq!(
+        m.edge_offset_and_seed
+            .iter()
+            .map(|x| x & offset_mask)
+            .collect::<Vec<_>>(),
+        j.edge_offset_and_seed
+            .iter()
+            .map(|x| x & offset_mask)
+            .collect::<Vec<_>>()
+    );
+
+    let mut s = HashSet::new();
+    for key in &keys {
+        let p = m.get_byte_array(key.as_bytes());
+        assert!(p < m.size());
+        assert!(s.insert(p));
+    }
+
+    // Round trip through the C-compatible format
+    let l = sux::mph::gov::GOVMPH::load_reader(dump.as_slice())?;
+    assert_eq!(l.edge_offset_and_seed, m.edge_offset_and_seed);
+    assert_eq!(l.array, m.array);
+    for key in &keys {
+        assert_eq!(
+            l.get_byte_array(key.as_bytes()),
+            m.get_byte_array(key.as_bytes())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gov_mph_build_small() -> Result<()> {
+    for n in (0..20).chain([100, 1501, 3001]) {
+        let m =
+            sux::mph::gov::GOVMPHBuilder::new(n as u64).build((0..n).map(usize::to_le_bytes))?;
+        let mut s = HashSet::new();
+        for i in 0..n {
+            let p = m.get_byte_array(&i.to_le_bytes());
+            assert!(p < m.size());
+            assert!(s.insert(p));
+        }
+    }
+
+    assert!(sux::mph::gov::GOVMPHBuilder::new(0)
+        .build(["a", "b", "a"])
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_gov3_sf_build() -> Result<()> {
+    let keys = BufReader::new(File::open("tests/data/mph.txt")?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let values = (0..keys.len() as u64).collect::<Vec<_>>();
+    let m = sux::sf::gov3::GOV3::new(&keys, &values, 14)?;
+    for (key, &value) in keys.iter().zip(values.iter()) {
+        assert_eq!(m.get_byte_array(key.as_bytes()), value);
+    }
+
+    // Round trip through the C-compatible format
+    let mut dump = Vec::new();
+    m.dump_writer(&mut dump)?;
+    let l = sux::sf::gov3::GOV3::load_reader(dump.as_slice())?;
+    assert_eq!(l.offset_and_seed, m.offset_and_seed);
+    assert_eq!(l.array, m.array);
+    for (key, &value) in keys.iter().zip(values.iter()) {
+        assert_eq!(l.get_byte_array(key.as_bytes()), value);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gov3_sf_build_small() -> Result<()> {
+    for width in [1, 7, 64] {
+        for n in (0..20).chain([100, 1501, 3001]) {
+            let keys = (0..n).map(usize::to_le_bytes).collect::<Vec<_>>();
+            let values = (0..n as u64)
+                .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) >> (64 - width))
+                .collect::<Vec<_>>();
+            let m = sux::sf::gov3::GOV3::new(&keys, &values, width)?;
+            for (key, &value) in keys.iter().zip(values.iter()) {
+                assert_eq!(m.get_byte_array(key), value);
+            }
+        }
+    }
+
+    assert!(sux::sf::gov3::GOV3::new(&["a", "b", "a"], &[0, 1, 2], 2).is_err());
+    assert!(sux::sf::gov3::GOV3::new(&["a", "b"], &[0, 4], 2).is_err());
+    assert!(sux::sf::gov3::GOV3::new(&["a", "b"], &[0], 2).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_gov_epserde() -> Result<()> {
+    let m = sux::mph::gov::GOVMPH::load("tests/data/test.cmph")?;
+    let tmp_file = std::env::temp_dir().join("test_serdes_gov_mph.bin");
+    let mut file = std::io::BufWriter::new(File::create(&tmp_file)?);
+    m.serialize(&mut file)?;
+    drop(file);
+    let c = <sux::mph::gov::GOVMPH>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+
+    let f = sux::sf::gov3::GOV3::load("tests/data/test.csf")?;
+    let tmp_file = std::env::temp_dir().join("test_serdes_gov3_sf.bin");
+    let mut file = std::io::BufWriter::new(File::create(&tmp_file)?);
+    f.serialize(&mut file)?;
+    drop(file);
+    let g = <sux::sf::gov3::GOV3>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+
+    assert_eq!(c.size(), m.size());
+    for line in BufReader::new(File::open("tests/data/mph.txt")?).lines() {
+        let line = line?;
+        assert_eq!(
+            c.get_byte_array(line.as_bytes()),
+            m.get_byte_array(line.as_bytes())
+        );
+        assert_eq!(
+            g.get_byte_array(line.as_bytes()),
+            f.get_byte_array(line.as_bytes())
+        );
+    }
+    Ok(())
+}
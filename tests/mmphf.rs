@@ -0,0 +1,72 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use std::io::prelude::*;
+use std::io::BufReader;
+use sux::mph::mmphf::LcpMMPH;
+use sux::prelude::*;
+
+#[test]
+fn test_lcp_mmph() -> Result<()> {
+    let words = BufReader::new(std::fs::File::open("tests/data/wordlist.10000")?)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let mut rcab = <RearCodedListBuilder>::new(8);
+    rcab.extend(words.iter());
+    let rca = rcab.build();
+
+    let mmph = LcpMMPH::new(&words)?;
+    assert_eq!(mmph.len(), words.len());
+    // The function returns the index of each string in the list
+    for (i, word) in rca.iter().enumerate() {
+        assert_eq!(mmph.get(&word), i);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lcp_mmph_small() -> Result<()> {
+    for n in 0..100 {
+        // Keys sharing long prefixes, some of them prefixes of others
+        let mut keys = (0..n)
+            .map(|i: usize| format!("{:b}", i + 1).into_bytes())
+            .collect::<Vec<_>>();
+        keys.sort();
+        let mmph = LcpMMPH::new(&keys)?;
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(mmph.get(key), i);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lcp_mmph_errors() {
+    assert!(LcpMMPH::new(&["b", "a"].map(String::from)).is_err());
+    assert!(LcpMMPH::new(&["a", "a"].map(String::from)).is_err());
+    assert!(LcpMMPH::new(&["a", "a\0b"].map(String::from)).is_err());
+}
+
+#[test]
+fn test_lcp_mmph_epserde() -> Result<()> {
+    let keys = (0..1000).map(|i| format!("{:06}", i)).collect::<Vec<_>>();
+    let mmph = LcpMMPH::new(&keys)?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_mmphf.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    mmph.serialize(&mut file)?;
+    drop(file);
+
+    let c = <LcpMMPH<String>>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(c.get(key), i);
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_rank9() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 511, 512, 513, 1000, 4096, 10000] {
+        for density in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            let mut bits = BitVec::new(len);
+            for i in 0..len {
+                bits.set(i, rng.gen_bool(density));
+            }
+            let bits: CountBitVec = bits.into();
+            let rank9: Rank9 = bits.convert_to()?;
+
+            let mut ranks = Vec::with_capacity(len + 1);
+            let mut ones = 0;
+            for i in 0..len {
+                ranks.push(ones);
+                if rank9.as_ref()[i / 64] >> (i % 64) & 1 != 0 {
+                    ones += 1;
+                }
+            }
+            ranks.push(ones);
+
+            assert_eq!(rank9.len(), len);
+            assert_eq!(rank9.count(), ones);
+            for (pos, &rank) in ranks.iter().enumerate() {
+                assert_eq!(rank9.rank(pos), rank);
+                assert_eq!(rank9.rank_zero(pos), pos - rank);
+            }
+            assert_eq!(rank9.rank(len + 1), ones);
+
+            // Select is forwarded
+            for pos in 0..len {
+                if ranks[pos + 1] != ranks[pos] {
+                    assert_eq!(rank9.select(ranks[pos]), Some(pos));
+                } else {
+                    assert_eq!(rank9.select_zero(pos - ranks[pos]), Some(pos));
+                }
+            }
+            assert_eq!(rank9.select(ones), None);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rank9_bit_vec() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 10000;
+    let mut bits = BitVec::new(len);
+    for i in 0..len {
+        bits.set(i, rng.gen_bool(0.3));
+    }
+    let expected = (0..=len)
+        .map(|pos| (0..pos).filter(|&i| bits.get(i)).count())
+        .collect::<Vec<_>>();
+    let rank9: Rank9<BitVec> = bits.convert_to()?;
+    for (pos, &rank) in expected.iter().enumerate() {
+        assert_eq!(rank9.rank(pos), rank);
+    }
+    assert_eq!(rank9.count(), expected[len]);
+
+    // Forget the index
+    let bits: BitVec = rank9.convert_to()?;
+    assert_eq!(bits.count_ones(), expected[len]);
+    Ok(())
+}
+
+#[test]
+fn test_rank9_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1000;
+    let mut bits = BitVec::new(len);
+    for i in 0..len {
+        bits.set(i, rng.gen_bool(0.5));
+    }
+    let bits: CountBitVec = bits.into();
+    let rank9: Rank9 = bits.convert_to()?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_rank9.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    rank9.serialize(&mut file)?;
+    drop(file);
+
+    let c = <Rank9>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+
+    for pos in 0..=len {
+        assert_eq!(rank9.rank(pos), c.rank(pos));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_simple_select() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 1000, 10000, 100000] {
+        for density in [0.0, 0.0001, 0.01, 0.1, 0.5, 0.9, 1.0] {
+            let mut bits = BitVec::new(len);
+            for i in 0..len {
+                bits.set(i, rng.gen_bool(density));
+            }
+            let ones = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+            let bits: CountBitVec = bits.into();
+            let simple: SimpleSelect<CountBitVec> = bits.convert_to()?;
+
+            assert_eq!(simple.len(), len);
+            assert_eq!(simple.count(), ones.len());
+            for (rank, &pos) in ones.iter().enumerate() {
+                assert_eq!(simple.select(rank), Some(pos));
+            }
+            assert_eq!(simple.select(ones.len()), None);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_simple_select_sparse_regions() -> Result<()> {
+    // Dense regions separated by large gaps, so that some blocks are spilled
+    let mut rng = SmallRng::seed_from_u64(0);
+    let len = 1 << 22;
+    let mut bits = BitVec::new(len);
+    for i in 0..len {
+        let dense = (i >> 16) % 8 == 0;
+        bits.set(i, rng.gen_bool(if dense { 0.5 } else { 0.0001 }));
+    }
+    let ones = (0..len).filter(|&i| bits.get(i)).collect::<Vec<_>>();
+    let bits: CountBitVec = bits.into();
+    let simple: SimpleSelect<Rank9> = bits.convert_to().and_then(|r: Rank9| r.convert_to())?;
+
+    for (rank, &pos) in ones.iter().enumerate() {
+        assert_eq!(simple.select(rank), Some(pos));
+        assert_eq!(simple.rank(pos), rank);
+    }
+    Ok(())
+}
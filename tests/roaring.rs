@@ -0,0 +1,122 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+const CHUNK: usize = 1 << 16;
+
+/// Return a bit vector whose chunks have very different densities.
+fn skewed(rng: &mut SmallRng) -> BitVec {
+    let len = 6 * CHUNK + 1000;
+    let mut bits = BitVec::new(len);
+    for i in 0..len {
+        let value = match i / CHUNK {
+            // Sparse
+            0 => rng.gen_bool(0.001),
+            // Empty
+            1 => false,
+            // Dense
+            2 => rng.gen_bool(0.5),
+            // Long runs
+            3 => (i / 1000) % 2 == 0,
+            // Full
+            4 => true,
+            _ => rng.gen_bool(0.3),
+        };
+        bits.set(i, value);
+    }
+    bits
+}
+
+#[test]
+fn test_roaring() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let bits = skewed(&mut rng);
+    let len = bits.len();
+    let ones = bits.iter_ones().collect::<Vec<_>>();
+    let zeros = bits.iter_zeros().collect::<Vec<_>>();
+
+    let roaring: RoaringBitVec = bits.clone().convert_to()?;
+    let (arrays, bitmaps, runs) = roaring.container_stats();
+    assert_eq!(arrays, 2);
+    assert_eq!(bitmaps, 2);
+    assert_eq!(runs, 2);
+    assert_eq!(roaring.len(), len);
+    assert_eq!(roaring.count(), ones.len());
+    assert_eq!(roaring.iter_ones().collect::<Vec<_>>(), ones);
+
+    let from_ones = RoaringBitVec::from_ones(len, &ones)?;
+    assert_eq!(from_ones.iter_ones().collect::<Vec<_>>(), ones);
+
+    let decoded: BitVec = roaring.clone().convert_to()?;
+    assert_eq!(decoded.as_ref(), bits.as_ref());
+
+    let rank9: Rank9<CountBitVec> = CountBitVec::from(bits).convert_to()?;
+    for pos in (0..=len).step_by(97).chain([CHUNK - 1, CHUNK, 3 * CHUNK, len]) {
+        assert_eq!(roaring.rank(pos), rank9.rank(pos));
+        assert_eq!(roaring.rank_zero(pos), rank9.rank_zero(pos));
+    }
+    assert_eq!(roaring.rank(len + 1), ones.len());
+    for rank in (0..ones.len()).step_by(31) {
+        assert_eq!(roaring.select(rank), Some(ones[rank]));
+    }
+    assert_eq!(roaring.select(ones.len()), None);
+    for rank in (0..zeros.len()).step_by(31) {
+        assert_eq!(roaring.select_zero(rank), Some(zeros[rank]));
+    }
+    assert_eq!(roaring.select_zero(zeros.len() - 1), zeros.last().copied());
+    assert_eq!(roaring.select_zero(zeros.len()), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_roaring_set_ops() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let a = skewed(&mut rng);
+    let b = skewed(&mut rng);
+    let ra: RoaringBitVec = a.clone().convert_to()?;
+    let rb: RoaringBitVec = b.clone().convert_to()?;
+
+    for (roaring, expected) in [
+        (ra.and(&rb), a.and(&b)),
+        (ra.or(&rb), a.or(&b)),
+        (ra.xor(&rb), a.xor(&b)),
+        (ra.and_not(&rb), a.and_not(&b)),
+        (ra.xor(&ra), BitVec::new(a.len())),
+    ] {
+        assert_eq!(roaring.count(), expected.count_ones());
+        let decoded: BitVec = roaring.convert_to()?;
+        assert_eq!(decoded.as_ref(), expected.as_ref());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_roaring_errors() {
+    assert!(RoaringBitVec::from_ones(10, &[1, 1]).is_err());
+    assert!(RoaringBitVec::from_ones(10, &[10]).is_err());
+}
+
+#[test]
+fn test_roaring_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let roaring: RoaringBitVec = skewed(&mut rng).convert_to()?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_roaring.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    roaring.serialize(&mut file)?;
+    drop(file);
+
+    let c = <RoaringBitVec>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    assert!(c.iter_ones().eq(roaring.iter_ones()));
+    Ok(())
+}
@@ -0,0 +1,75 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_rrr() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 62, 63, 64, 126, 2016, 2017, 4000] {
+        for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+            let mut bits = BitVec::new(len);
+            for i in 0..len {
+                bits.set(i, rng.gen_bool(density));
+            }
+            let expected = bits.iter_ones().collect::<Vec<_>>();
+            let zeros = bits.iter_zeros().collect::<Vec<_>>();
+            let rrr: RRRBitVec = bits.clone().convert_to()?;
+
+            assert_eq!(rrr.len(), len);
+            assert_eq!(rrr.count(), expected.len());
+            assert_eq!(rrr.iter_ones().collect::<Vec<_>>(), expected);
+
+            let mut rank = 0;
+            for pos in 0..len {
+                assert_eq!(rrr.rank(pos), rank);
+                assert_eq!(rrr.rank_zero(pos), pos - rank);
+                if bits.get(pos) {
+                    rank += 1;
+                }
+            }
+            assert_eq!(rrr.rank(len), rank);
+            assert_eq!(rrr.rank(len + 1), rank);
+
+            for (rank, &pos) in expected.iter().enumerate() {
+                assert_eq!(rrr.select(rank), Some(pos));
+            }
+            assert_eq!(rrr.select(expected.len()), None);
+            for (rank, &pos) in zeros.iter().enumerate() {
+                assert_eq!(rrr.select_zero(rank), Some(pos));
+            }
+            assert_eq!(rrr.select_zero(zeros.len()), None);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rrr_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bits = BitVec::new(1000);
+    for i in 0..1000 {
+        bits.set(i, rng.gen_bool(0.1));
+    }
+    let rrr: RRRBitVec = bits.convert_to()?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_rrr.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    rrr.serialize(&mut file)?;
+    drop(file);
+
+    let c = <RRRBitVec>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    for pos in 0..=1000 {
+        assert_eq!(c.rank(pos), rrr.rank(pos));
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// Check that two structures return the same results.
+fn check<A, B>(a: &A, b: &B)
+where
+    A: Rank + RankZero + Select + SelectZero,
+    B: Rank + RankZero + Select + SelectZero,
+{
+    assert_eq!(a.len(), b.len());
+    assert_eq!(a.count(), b.count());
+    for pos in 0..=a.len() + 1 {
+        assert_eq!(a.rank(pos), b.rank(pos));
+        assert_eq!(a.rank_zero(pos.min(a.len())), b.rank_zero(pos.min(b.len())));
+    }
+    for rank in 0..=a.count() {
+        assert_eq!(a.select(rank), b.select(rank));
+    }
+    for rank in 0..=a.len() - a.count() {
+        assert_eq!(a.select_zero(rank), b.select_zero(rank));
+    }
+}
+
+#[test]
+fn test_sparse_bit_vec() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000, 10000] {
+        for density in [0.0, 0.001, 0.05, 0.5, 1.0] {
+            let mut bits = BitVec::new(len);
+            for i in 0..len {
+                bits.set(i, rng.gen_bool(density));
+            }
+            let ones = bits.iter_ones().collect::<Vec<_>>();
+            let sparse: SparseBitVec = bits.clone().convert_to()?;
+            assert_eq!(sparse.iter_ones().collect::<Vec<_>>(), ones);
+
+            let dense: Rank9<CountBitVec> = CountBitVec::from(bits).convert_to()?;
+            check(&sparse, &dense);
+
+            let sparse = SparseBitVec::from_ones(len, &ones)?;
+            let sparse: SparseBitVec<QuantumIndex<CountBitVec>> = sparse.convert_to()?;
+            let sparse: SparseBitVec<QuantumZeroIndex<QuantumIndex<CountBitVec>>> =
+                sparse.convert_to()?;
+            check(&sparse, &dense);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sparse_bit_vec_errors() {
+    assert!(SparseBitVec::from_ones(10, &[1, 1]).is_err());
+    assert!(SparseBitVec::from_ones(10, &[2, 1]).is_err());
+    assert!(SparseBitVec::from_ones(10, &[10]).is_err());
+}
@@ -0,0 +1,160 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// A tree stored as lists of children, with nodes numbered in the order
+/// required by the succinct representation.
+struct Naive {
+    children: Vec<Vec<usize>>,
+    parents: Vec<Option<usize>>,
+}
+
+impl Naive {
+    /// Renumber a tree given as lists of children in the given order.
+    fn new(children: &[Vec<usize>], order: &[usize]) -> Self {
+        let mut id = vec![0; order.len()];
+        for (i, &node) in order.iter().enumerate() {
+            id[node] = i;
+        }
+        let children = order
+            .iter()
+            .map(|&node| children[node].iter().map(|&c| id[c]).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut parents = vec![None; order.len()];
+        for (node, c) in children.iter().enumerate() {
+            for &child in c {
+                parents[child] = Some(node);
+            }
+        }
+        Self { children, parents }
+    }
+
+    fn degrees(&self) -> Vec<usize> {
+        self.children.iter().map(|c| c.len()).collect()
+    }
+
+    fn depth(&self, mut node: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.parents[node] {
+            node = parent;
+            depth += 1;
+        }
+        depth
+    }
+
+    fn subtree_size(&self, node: usize) -> usize {
+        1 + self.children[node]
+            .iter()
+            .map(|&c| self.subtree_size(c))
+            .sum::<usize>()
+    }
+
+    fn next_sibling(&self, node: usize) -> Option<usize> {
+        let siblings = &self.children[self.parents[node]?];
+        let index = siblings.iter().position(|&c| c == node).unwrap();
+        <[usize]>::get(siblings, index + 1).copied()
+    }
+}
+
+/// Return a random tree as lists of children, and its breadth-first
+/// and depth-first orders.
+fn random_tree(rng: &mut SmallRng, n: usize) -> (Vec<Vec<usize>>, Vec<usize>, Vec<usize>) {
+    let mut children = vec![Vec::new(); n];
+    for node in 1..n {
+        // Attach preferably to recent nodes, to get deep trees
+        let parent = if rng.gen_bool(0.5) {
+            rng.gen_range(node.saturating_sub(5)..node)
+        } else {
+            rng.gen_range(0..node)
+        };
+        children[parent].push(node);
+    }
+
+    let mut bfs = vec![0];
+    let mut i = 0;
+    while i < bfs.len() {
+        bfs.extend(children[bfs[i]].iter().copied());
+        i += 1;
+    }
+
+    let mut dfs = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        dfs.push(node);
+        stack.extend(children[node].iter().rev().copied());
+    }
+
+    (children, bfs, dfs)
+}
+
+macro_rules! check_tree {
+    ($tree:expr, $naive:expr) => {
+        let (tree, naive) = (&$tree, &$naive);
+        let n = naive.children.len();
+        assert_eq!(tree.num_nodes(), n);
+        for node in 0..n {
+            let children = &naive.children[node];
+            assert_eq!(tree.degree(node), children.len());
+            assert_eq!(tree.parent(node), naive.parents[node]);
+            assert_eq!(tree.first_child(node), children.first().copied());
+            for (i, &child) in children.iter().enumerate() {
+                assert_eq!(tree.child(node, i), Some(child));
+            }
+            assert_eq!(tree.child(node, children.len()), None);
+            assert_eq!(tree.next_sibling(node), naive.next_sibling(node));
+            assert_eq!(tree.depth(node), naive.depth(node));
+            assert_eq!(tree.subtree_size(node), naive.subtree_size(node));
+        }
+    };
+}
+
+#[test]
+fn test_tree() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [1, 2, 3, 10, 100, 1000, 3000] {
+        let (children, bfs, dfs) = random_tree(&mut rng, n);
+
+        let naive = Naive::new(&children, &bfs);
+        let louds = Louds::from_degrees(naive.degrees())?;
+        check_tree!(louds, naive);
+
+        let naive = Naive::new(&children, &dfs);
+        let dfuds = Dfuds::from_degrees(naive.degrees())?;
+        check_tree!(dfuds, naive);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_tree_errors() {
+    assert!(Louds::from_degrees([]).is_err());
+    assert!(Louds::from_degrees([2, 0]).is_err());
+    assert!(Louds::from_degrees([1, 0, 0]).is_err());
+    assert!(Dfuds::from_degrees([0, 1]).is_err());
+}
+
+#[test]
+fn test_tree_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (children, _, dfs) = random_tree(&mut rng, 1000);
+    let naive = Naive::new(&children, &dfs);
+    let dfuds = Dfuds::from_degrees(naive.degrees())?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_dfuds.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    dfuds.serialize(&mut file)?;
+    drop(file);
+
+    let c = <Dfuds>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    check_tree!(c, naive);
+    Ok(())
+}
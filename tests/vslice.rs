@@ -0,0 +1,165 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn collect(s: &impl VSlice) -> Vec<usize> {
+    (0..s.len()).map(|i| s.get(i)).collect()
+}
+
+fn check_view<S: VSlice + Sync>(s: &S, values: &[usize], rng: &mut SmallRng) {
+    let len = values.len();
+    for _ in 0..20 {
+        let start = rng.gen_range(0..=len);
+        let end = rng.gen_range(start..=len);
+        let view = s.slice(start..end);
+        assert_eq!(view.len(), end - start);
+        assert_eq!(view.bit_width(), s.bit_width());
+        assert_eq!(collect(&view), values[start..end]);
+        assert_eq!(view.iter_values().collect::<Vec<_>>(), values[start..end]);
+
+        // Nested slicing
+        let a = rng.gen_range(0..=view.len());
+        let b = rng.gen_range(a..=view.len());
+        let nested = view.slice(a..b);
+        assert_eq!(collect(&nested), values[start + a..start + b]);
+        assert_eq!(
+            nested.range(0..b - a).collect::<Vec<_>>(),
+            values[start + a..start + b]
+        );
+        let mut dst = vec![0; b - a];
+        nested.copy_to(&mut dst);
+        assert_eq!(dst, values[start + a..start + b]);
+
+        let (left, right) = view.split_at(a);
+        assert_eq!(collect(&left), values[start..start + a]);
+        assert_eq!(collect(&right), values[start + a..end]);
+
+        for chunk_size in [1, 3, 100] {
+            let chunks = view.chunks(chunk_size).collect::<Vec<_>>();
+            assert_eq!(chunks.len(), (end - start + chunk_size - 1) / chunk_size);
+            assert!(chunks.iter().all(|c| c.len() <= chunk_size));
+            assert_eq!(
+                chunks.iter().flat_map(collect).collect::<Vec<_>>(),
+                values[start..end]
+            );
+
+            #[cfg(feature = "rayon")]
+            {
+                use rayon::prelude::*;
+                let sums = view
+                    .par_chunks(chunk_size)
+                    .map(|c| c.iter_from(0).sum::<usize>())
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    sums,
+                    chunks
+                        .iter()
+                        .map(|c| collect(c).iter().sum::<usize>())
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_vslice_view() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 10, 1000] {
+        let values = (0..len).map(|_| rng.gen_range(0..1000)).collect::<Vec<_>>();
+        let cp = CompactArray::from_slice(&values);
+        check_view(&cp, &values, &mut rng);
+        check_view(&values, &values, &mut rng);
+    }
+}
+
+#[test]
+fn test_vslice_view_mut() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..1000)
+        .map(|_| rng.gen_range(0..1000))
+        .collect::<Vec<_>>();
+    let mut cp = CompactArray::from_slice(&values);
+
+    let mut view = cp.slice_mut(100..900);
+    assert_eq!(view.len(), 800);
+    view.set(0, 1);
+    let mut nested = view.slice_mut(10..20);
+    nested.set(9, 2);
+    assert_eq!(nested.get(9), 2);
+    assert_eq!(collect(&nested.slice(9..10)), [2]);
+    assert_eq!(view.get(19), 2);
+    values[100] = 1;
+    values[119] = 2;
+    assert_eq!(collect(&cp), values);
+
+    let mut copy = values.clone();
+    let mut view = copy.slice_mut(500..1000);
+    for i in 0..view.len() {
+        view.set(i, i);
+    }
+    for i in 0..500 {
+        values[500 + i] = i;
+    }
+    assert_eq!(copy, values);
+}
+
+#[test]
+fn test_vslice_view_mut_split() {
+    let mut values = (0..1000).collect::<Vec<usize>>();
+    let mut expected = values.clone();
+    let mut view = values.slice_mut(100..900);
+
+    let (mut left, mut right) = view.split_at_mut(300);
+    assert_eq!(left.len(), 300);
+    assert_eq!(right.len(), 500);
+    left.set(0, 1);
+    right.set(0, 2);
+    expected[100] = 1;
+    expected[400] = 2;
+
+    for (i, mut chunk) in view.chunks_mut(70).enumerate() {
+        assert!(chunk.len() <= 70);
+        chunk.set(chunk.len() - 1, i);
+        expected[100 + (i * 70 + 69).min(799)] = i;
+    }
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        let mut nested = view.slice_mut(10..20);
+        nested.par_chunks_mut(3).for_each(|chunk| {
+            for value in chunk.iter_mut() {
+                *value = 0;
+            }
+        });
+        for value in &mut expected[110..120] {
+            *value = 0;
+        }
+    }
+
+    assert_eq!(values, expected);
+}
+
+#[test]
+#[should_panic]
+fn test_vslice_view_out_of_bounds() {
+    let values = vec![0; 10];
+    let view = values.slice(2..8);
+    view.slice(3..7);
+}
+
+#[test]
+#[should_panic]
+fn test_vslice_view_mut_bit_width() {
+    let mut cp = CompactArray::new(3, 10);
+    let mut view = cp.slice_mut(2..8);
+    view.set(0, 8);
+}
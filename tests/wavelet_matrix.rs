@@ -0,0 +1,164 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+/// Return a random sequence with a skewed distribution of values.
+fn random_seq(rng: &mut SmallRng, bit_width: usize, len: usize) -> CompactArray {
+    let mut seq = CompactArray::new(bit_width, len);
+    let max = if bit_width == 64 {
+        usize::MAX
+    } else {
+        (1 << bit_width) - 1
+    };
+    for i in 0..len {
+        // Small values are frequent, so that top-k queries are meaningful
+        let value = if rng.gen_bool(0.5) {
+            rng.gen_range(0..=max.min(7))
+        } else {
+            rng.gen_range(0..=max)
+        };
+        seq.set(i, value);
+    }
+    seq
+}
+
+macro_rules! check_wavelet_matrix {
+    ($wm: expr, $values: expr, $rng: expr) => {
+        let wm = &$wm;
+        let values: &[usize] = &$values;
+        let len = values.len();
+        assert_eq!(wm.len(), len);
+
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(wm.access(i), value);
+            assert_eq!(wm.get(i), value);
+        }
+        assert_eq!(wm.iter_from(0).collect::<Vec<_>>(), values);
+        assert_eq!(
+            wm.range(len / 2..len).collect::<Vec<_>>(),
+            values[len / 2..]
+        );
+
+        let mut symbols = values.to_vec();
+        symbols.sort();
+        symbols.dedup();
+        // Add a value that does not occur
+        symbols.push(symbols.last().map_or(0, |&s| s.wrapping_add(1)));
+        for &symbol in &symbols {
+            let positions = values
+                .iter()
+                .enumerate()
+                .filter(|&(_, &v)| v == symbol)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            for pos in (0..=len).step_by(len / 20 + 1).chain([len]) {
+                assert_eq!(
+                    wm.rank(symbol, pos),
+                    positions.iter().filter(|&&p| p < pos).count()
+                );
+            }
+            for (rank, &p) in positions.iter().enumerate() {
+                assert_eq!(wm.select(symbol, rank), Some(p));
+            }
+            assert_eq!(wm.select(symbol, positions.len()), None);
+        }
+
+        for _ in 0..100 {
+            let start = $rng.gen_range(0..=len);
+            let end = $rng.gen_range(start..=len);
+            let mut sorted = values[start..end].to_vec();
+            sorted.sort();
+
+            let lower = symbols[$rng.gen_range(0..symbols.len())];
+            let upper = symbols[$rng.gen_range(0..symbols.len())];
+            assert_eq!(
+                wm.range_count(start..end, lower..upper),
+                sorted.iter().filter(|&&v| lower <= v && v < upper).count()
+            );
+
+            for rank in 0..=sorted.len() {
+                assert_eq!(
+                    wm.quantile(start..end, rank),
+                    <[usize]>::get(&sorted, rank).copied()
+                );
+            }
+
+            let mut counts = Vec::<(usize, usize)>::new();
+            for &v in &sorted {
+                match counts.last_mut() {
+                    Some((last, count)) if *last == v => *count += 1,
+                    _ => counts.push((v, 1)),
+                }
+            }
+            counts.sort_by_key(|&(v, count)| (std::cmp::Reverse(count), v));
+            for k in [0, 1, 3, counts.len() + 1] {
+                assert_eq!(
+                    wm.top_k(start..end, k),
+                    counts.iter().take(k).copied().collect::<Vec<_>>()
+                );
+            }
+        }
+    };
+}
+
+#[test]
+fn test_wavelet_matrix() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 10, 64] {
+        for len in [0, 1, 10, 100, 1000] {
+            let seq = random_seq(&mut rng, bit_width, len);
+            let values = (0..len).map(|i| seq.get(i)).collect::<Vec<_>>();
+            let wm = WaveletMatrix::new(&seq)?;
+            assert_eq!(wm.bit_width(), bit_width);
+            check_wavelet_matrix!(wm, values, rng);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_wavelet_matrix_slice() -> Result<()> {
+    let values = vec![3, 0, usize::MAX, 3, 1 << 40, 3];
+    let wm = WaveletMatrix::new(&values.as_slice())?;
+    assert_eq!(wm.bit_width(), 64);
+    assert_eq!(wm.rank(3, 6), 3);
+    assert_eq!(wm.select(usize::MAX, 0), Some(2));
+    assert_eq!(wm.quantile(0..6, 5), Some(usize::MAX));
+    assert_eq!(wm.range_count(0..6, 1..usize::MAX), 4);
+    assert_eq!(wm.top_k(0..5, 1), vec![(3, 2)]);
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn test_wavelet_matrix_range_out_of_bounds() {
+    let values = vec![1, 2, 3];
+    let wm = WaveletMatrix::new(&values.as_slice()).unwrap();
+    wm.quantile(1..4, 0);
+}
+
+#[test]
+fn test_wavelet_matrix_epserde() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let seq = random_seq(&mut rng, 10, 1000);
+    let values = (0..1000).map(|i| seq.get(i)).collect::<Vec<_>>();
+    let wm = WaveletMatrix::new(&seq)?;
+
+    let tmp_file = std::env::temp_dir().join("test_serdes_wavelet_matrix.bin");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    wm.serialize(&mut file)?;
+    drop(file);
+
+    let c = <WaveletMatrix>::mmap(&tmp_file, epserde::des::Flags::empty())?;
+    check_wavelet_matrix!(c, values, rng);
+    Ok(())
+}